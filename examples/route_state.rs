@@ -0,0 +1,38 @@
+//! 把事件路由到结构体的字段中
+use kbdmshook::{
+    set_hook_callback_with, start_hook_async, stop_and_join, Dispatch, Event, KeyEvent,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Default)]
+struct App {
+    key_presses: Mutex<u32>,
+    mouse_events: Mutex<u32>,
+}
+
+impl App {
+    fn on_event(&self, event: Event) -> Dispatch {
+        match event {
+            Event::KeyEvent(KeyEvent::KeyPress(_)) => *self.key_presses.lock().unwrap() += 1,
+            Event::KeyEvent(KeyEvent::KeyUp(_)) => (),
+            Event::MouseEvent(_) => *self.mouse_events.lock().unwrap() += 1,
+            _ => (),
+        }
+        Dispatch::Pass
+    }
+}
+
+fn main() -> kbdmshook::Result<()> {
+    let app = Arc::new(App::default());
+    set_hook_callback_with(app.clone(), App::on_event)?;
+    let handle = start_hook_async(true, true);
+    std::thread::sleep(Duration::from_secs(5));
+    stop_and_join(handle)?;
+    println!(
+        "key presses: {}, mouse events: {}",
+        app.key_presses.lock().unwrap(),
+        app.mouse_events.lock().unwrap()
+    );
+    Ok(())
+}
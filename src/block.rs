@@ -0,0 +1,20 @@
+use crate::{chord::is_part_of, sync::RwLock, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+
+static BLOCKED_KEYS: Lazy<RwLock<HashSet<u32>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+/// 设置全局屏蔽的按键，钩子运行期间这些按键的按下和抬起都不会传递给其他程序，例如屏蔽Win键、Caps Lock
+///
+/// 回调仍然会收到这些按键的事件，可以记录按键尝试。`vk::SHIFT`等不区分左右的键码匹配任意一侧。
+/// 暂停期间不屏蔽。屏蔽的按键仍然计入修饰键状态，紧急停止组合键(`HookConfig::kill_switch`)总是有效，
+/// 不会因为屏蔽了按键而无法停止钩子。传入空集合取消屏蔽。
+pub fn set_blocked_keys(keys: HashSet<u32>) -> Result<()> {
+    *BLOCKED_KEYS.write() = keys;
+    Ok(())
+}
+
+pub(crate) fn is_blocked(vk: u32) -> bool {
+    let keys = BLOCKED_KEYS.read();
+    !keys.is_empty() && keys.iter().any(|&key| is_part_of(key, vk))
+}
@@ -0,0 +1,106 @@
+use crate::{call_guarded, sync::Mutex, vk, Result, KEYS_DOWN};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+type ChordAction = Arc<Mutex<Box<dyn Fn() + Send>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChordId(u64);
+
+struct Chord {
+    id: ChordId,
+    keys: Vec<u32>,
+    //已经触发，所有按键抬起后才能再次触发
+    fired: bool,
+    action: ChordAction,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static CHORDS: Lazy<Mutex<Vec<Chord>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 注册组合键，`keys`中的按键同时按下时执行`action`，与按下的顺序无关
+///
+/// 与热键不同，组合键中的按键没有修饰键的含义，`vk::SHIFT`等不区分左右的键码匹配任意一侧。
+/// 触发后需要组合中的按键全部抬起才能再次触发。一次按键同时凑齐多个组合键时只触发按键最多的一个，
+/// 例如同时注册了A+D和A+D+S，先按住A、S再按D时只触发A+D+S。
+pub fn register_chord(keys: &[u32], action: impl Fn() + Send + 'static) -> Result<ChordId> {
+    let id = ChordId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let mut keys = keys.to_vec();
+    keys.sort_unstable();
+    keys.dedup();
+    CHORDS.lock().push(Chord {
+        id,
+        keys,
+        fired: false,
+        action: Arc::new(Mutex::new(Box::new(action))),
+    });
+    Ok(id)
+}
+
+pub fn unregister_chord(id: ChordId) -> Result<()> {
+    CHORDS.lock().retain(|chord| chord.id != id);
+    Ok(())
+}
+
+fn held(keys_down: &HashSet<u32>, key: u32) -> bool {
+    let sides = match key {
+        vk::SHIFT => [vk::LSHIFT, vk::RSHIFT],
+        vk::CONTROL => [vk::LCONTROL, vk::RCONTROL],
+        vk::MENU => [vk::LMENU, vk::RMENU],
+        _ => return keys_down.contains(&key),
+    };
+    sides.iter().any(|vk| keys_down.contains(vk))
+}
+
+/// `key`是否匹配按下的`vk`，不区分左右的修饰键匹配任意一侧
+pub(crate) fn is_part_of(key: u32, vk: u32) -> bool {
+    key == vk
+        || matches!(
+            (key, vk),
+            (vk::SHIFT, vk::LSHIFT | vk::RSHIFT)
+                | (vk::CONTROL, vk::LCONTROL | vk::RCONTROL)
+                | (vk::MENU, vk::LMENU | vk::RMENU)
+        )
+}
+
+/// 在键盘钩子中更新按下的按键集合之后调用
+pub(crate) fn process_key(vk: u32, down: bool) {
+    let keys_down: HashSet<u32> = KEYS_DOWN.read().keys().copied().collect();
+    //先取出要执行的动作再释放锁，动作中可以注册/注销组合键
+    let action = {
+        let mut chords = CHORDS.lock();
+        if !down {
+            for chord in chords.iter_mut() {
+                if chord.fired && !chord.keys.iter().any(|&key| held(&keys_down, key)) {
+                    chord.fired = false;
+                }
+            }
+            return;
+        }
+        let mut action: Option<(usize, ChordAction)> = None;
+        for chord in chords.iter_mut() {
+            let completed = chord.keys.iter().any(|&key| is_part_of(key, vk))
+                && chord.keys.iter().all(|&key| held(&keys_down, key));
+            if !completed || chord.fired {
+                continue;
+            }
+            //同时凑齐的子集组合键也标记为已触发，避免之后单独触发
+            chord.fired = true;
+            if action
+                .as_ref()
+                .is_none_or(|(len, _)| chord.keys.len() > *len)
+            {
+                action = Some((chord.keys.len(), chord.action.clone()));
+            }
+        }
+        match action {
+            Some((_, action)) => action,
+            None => return,
+        }
+    };
+    call_guarded(&*action.lock());
+}
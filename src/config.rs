@@ -0,0 +1,304 @@
+use crate::{vk, Modifiers, ProcessMatch};
+use std::time::Duration;
+use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_SWAPBUTTON};
+
+/// 鼠标左右键的对应方式，同时用于钩子上报的事件和`click`等注入函数
+///
+/// 低级钩子收到的是物理按键，系统设置了左右键互换时按下物理右键仍然上报为`MouseRightButtonDown`；
+/// SendInput注入的也是物理按键，系统会再按设置互换。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ButtonSwap {
+    /// 上报和注入物理按键
+    #[default]
+    Physical,
+    /// 按系统设置(SM_SWAPBUTTON)互换，上报和注入逻辑按键，`Left`始终是主按键
+    System,
+    /// 始终互换左右键
+    Always,
+}
+
+impl ButtonSwap {
+    /// 当前是否需要互换左右键
+    pub(crate) fn is_swapped(self) -> bool {
+        match self {
+            ButtonSwap::Physical => false,
+            ButtonSwap::System => unsafe { GetSystemMetrics(SM_SWAPBUTTON) != 0 },
+            ButtonSwap::Always => true,
+        }
+    }
+}
+
+/// 钩子配置，通过`start_hook_with`启动
+#[derive(Debug, Clone)]
+pub struct HookConfig {
+    /// 安装鼠标钩子
+    pub mouse: bool,
+    /// 安装键盘钩子
+    pub keyboard: bool,
+    /// 按住按键时不派发系统自动重复的按下事件
+    pub suppress_repeats: bool,
+    /// 同一按键连续两次按下时额外派发`MouseLeftDoubleClick`等双击事件
+    pub double_clicks: bool,
+    /// 双击的最大间隔，None使用系统设置(GetDoubleClickTime)
+    pub double_click_time: Option<Duration>,
+    /// 两次按下在x、y方向允许的最大距离(像素)，None使用系统设置(SM_CXDOUBLECLK/SM_CYDOUBLECLK)
+    pub double_click_distance: Option<(i32, i32)>,
+    /// 为false时不派发`MouseMove`，鼠标钩子仍然会调用CallNextHookEx
+    pub report_mouse_moves: bool,
+    /// 距离上一次派发的移动事件不足该间隔时丢弃`MouseMove`，按键、滚轮事件不受影响
+    pub mouse_move_throttle: Option<Duration>,
+    /// 派发`Event::HookStarted`/`Event::HookStopped`
+    pub lifecycle_events: bool,
+    /// 鼠标按下时通过WindowFromPoint获取按下位置的窗口，填充`MouseInfo::window`
+    pub window_info: bool,
+    /// 紧急停止组合键(修饰键, 虚拟键码)，按下时无论回调是否拦截都会调用`stop_hook`，
+    /// 在防抖、`suppress_next`和重映射之前判断，该按键直接传递给下一个钩子，不派发给回调。
+    /// 默认Ctrl+Alt+Shift+F12，None表示不启用
+    pub kill_switch: Option<(Modifiers, u32)>,
+    /// 丢弃所有带LLKHF_INJECTED/LLMHF_INJECTED标志的注入事件，不派发给回调也不触发热键
+    pub ignore_injected: bool,
+    /// 只丢弃本库注入的事件(dwExtraInfo为`INJECTED_EXTRA_INFO`)，避免回调中注入输入导致循环
+    pub ignore_own_injected: bool,
+    /// 保存最近派发的事件数量，可以通过`recent_events`读取，0表示不保存
+    pub recent_events: usize,
+    /// 按住按键移动时额外派发`DragStart`/`DragMove`/`DragEnd`事件
+    pub drags: bool,
+    /// 开始拖动前在x、y方向需要移动的距离(像素)，None使用系统设置(SM_CXDRAG/SM_CYDRAG)
+    pub drag_threshold: Option<(i32, i32)>,
+    /// 在`MouseMove`的`MouseInfo::dx`/`dy`中提供相对上一次派发的移动事件的移动量
+    pub mouse_move_deltas: bool,
+    /// 同一按键(鼠标按键)抬起后在该间隔内再次按下视为抖动，拦截这次按下和对应的抬起，
+    /// 不派发也不传递给其他程序。按住不放时的自动重复不受影响，注入的事件不做处理
+    pub debounce: Option<Duration>,
+    /// 鼠标按键事件中通过`MouseInfo::client_pos`提供相对前台窗口客户区的坐标
+    pub client_pos: bool,
+    /// 累计滚轮滚动量，每满一格额外派发`MouseWheelNotch`/`MouseHWheelNotch`，原始滚轮事件照常派发
+    pub wheel_notches: bool,
+    /// 鼠标左右键的对应方式，默认上报物理按键
+    pub swap_buttons: ButtonSwap,
+    /// 在单独的工作线程中调用回调，值为队列容量，None时在钩子线程中同步调用
+    ///
+    /// 钩子函数执行超过注册表`HKCU\Control Panel\Desktop\LowLevelHooksTimeout`(默认约300ms)时，
+    /// 系统会跳过这次调用，多次超时后会静默卸载钩子。截图、写文件等耗时的回调建议启用该选项，
+    /// 钩子函数只把事件放入队列后立即返回。此时回调的返回值不再起作用，无法拦截事件；
+    /// 队列已满时丢弃最新的事件，丢弃数量可以通过`dropped_callback_events`读取。
+    /// 热键、监听器、通道等仍然在钩子线程中处理。容量为0时按1处理，创建线程失败时启动钩子
+    /// 返回`KbdmsError::WorkerSpawn`。
+    pub callback_worker: Option<usize>,
+    /// 一次事件的回调和监听器执行超过该时间时输出警告并派发`Event::CallbackSlow`，默认None不检测。
+    /// 与`lifecycle_events`一样需要主动开启，避免已有的回调收到新的事件，建议设为200ms左右，
+    /// 低于LowLevelHooksTimeout的默认值
+    pub slow_callback_threshold: Option<Duration>,
+    /// 按该间隔通过`verify_hooks`检查钩子，被系统卸载时重新安装并派发`Event::HookReinstalled`，
+    /// 只在钩子自己的消息循环中生效，`install_hooks`需要调用者自行检查
+    pub reinstall_hooks: Option<Duration>,
+    /// 只在前台窗口匹配时派发事件，不匹配时事件照常传递给下一个钩子，热键也不会触发
+    pub only_when_foreground: Option<ProcessMatch>,
+    /// 每秒派发的事件超过该数量时丢弃之后的移动事件，超过两倍时同时丢弃滚轮事件，
+    /// 按键事件总是派发。启用`callback_worker`时队列积压一半以上也视为过载。
+    /// 丢弃的事件照常传递给下一个钩子，每秒最多派发一次`Event::Overload`汇总。
+    /// 默认None不限制，与`lifecycle_events`一样需要主动开启，例如设为5000
+    pub overload_limit: Option<u32>,
+    /// 丢弃位置与上一次派发的`MouseMove`完全相同的移动事件，按键、滚轮事件不受影响
+    pub dedup_mouse_moves: bool,
+    /// 允许回调拦截`MouseMove`，默认false，拦截移动事件会让光标停在原地，系统看起来像卡死
+    pub block_mouse_move: bool,
+    /// 只钩当前进程中的一个线程(线程ID)，使用WH_KEYBOARD/WH_MOUSE代替全局的低级钩子，默认None
+    ///
+    /// 钩子函数在目标线程取出输入消息时由该线程调用，回调也在该线程执行，该线程需要有消息循环。
+    /// 只能收到发给该线程窗口的输入，对系统其他程序没有影响。
+    /// 线程钩子得不到注入标志，`EventSource`总是`Physical`；`reinstall_hooks`的检测依赖全局输入，
+    /// 不会触发重新安装。
+    pub target_thread: Option<u32>,
+}
+
+impl Default for HookConfig {
+    fn default() -> Self {
+        HookConfig {
+            mouse: true,
+            keyboard: true,
+            suppress_repeats: false,
+            double_clicks: false,
+            double_click_time: None,
+            double_click_distance: None,
+            report_mouse_moves: true,
+            mouse_move_throttle: None,
+            lifecycle_events: false,
+            window_info: false,
+            kill_switch: Some((
+                Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT,
+                vk::F12,
+            )),
+            ignore_injected: false,
+            ignore_own_injected: false,
+            recent_events: 0,
+            drags: false,
+            drag_threshold: None,
+            mouse_move_deltas: false,
+            debounce: None,
+            client_pos: false,
+            wheel_notches: false,
+            swap_buttons: ButtonSwap::Physical,
+            callback_worker: None,
+            slow_callback_threshold: None,
+            reinstall_hooks: None,
+            only_when_foreground: None,
+            overload_limit: None,
+            dedup_mouse_moves: false,
+            block_mouse_move: false,
+            target_thread: None,
+        }
+    }
+}
+
+impl HookConfig {
+    /// 默认配置：安装鼠标和键盘钩子
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mouse(mut self, mouse: bool) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
+    pub fn keyboard(mut self, keyboard: bool) -> Self {
+        self.keyboard = keyboard;
+        self
+    }
+
+    pub fn suppress_repeats(mut self, suppress_repeats: bool) -> Self {
+        self.suppress_repeats = suppress_repeats;
+        self
+    }
+
+    pub fn double_clicks(mut self, double_clicks: bool) -> Self {
+        self.double_clicks = double_clicks;
+        self
+    }
+
+    pub fn double_click_time(mut self, time: Duration) -> Self {
+        self.double_click_time = Some(time);
+        self
+    }
+
+    pub fn double_click_distance(mut self, dx: i32, dy: i32) -> Self {
+        self.double_click_distance = Some((dx, dy));
+        self
+    }
+
+    pub fn report_mouse_moves(mut self, report_mouse_moves: bool) -> Self {
+        self.report_mouse_moves = report_mouse_moves;
+        self
+    }
+
+    pub fn mouse_move_throttle(mut self, throttle: Duration) -> Self {
+        self.mouse_move_throttle = Some(throttle);
+        self
+    }
+
+    pub fn lifecycle_events(mut self, lifecycle_events: bool) -> Self {
+        self.lifecycle_events = lifecycle_events;
+        self
+    }
+
+    pub fn window_info(mut self, window_info: bool) -> Self {
+        self.window_info = window_info;
+        self
+    }
+
+    pub fn kill_switch(mut self, kill_switch: Option<(Modifiers, u32)>) -> Self {
+        self.kill_switch = kill_switch;
+        self
+    }
+
+    pub fn ignore_injected(mut self, ignore_injected: bool) -> Self {
+        self.ignore_injected = ignore_injected;
+        self
+    }
+
+    pub fn ignore_own_injected(mut self, ignore_own_injected: bool) -> Self {
+        self.ignore_own_injected = ignore_own_injected;
+        self
+    }
+
+    pub fn recent_events(mut self, capacity: usize) -> Self {
+        self.recent_events = capacity;
+        self
+    }
+
+    pub fn drags(mut self, drags: bool) -> Self {
+        self.drags = drags;
+        self
+    }
+
+    pub fn drag_threshold(mut self, dx: i32, dy: i32) -> Self {
+        self.drag_threshold = Some((dx, dy));
+        self
+    }
+
+    pub fn mouse_move_deltas(mut self, mouse_move_deltas: bool) -> Self {
+        self.mouse_move_deltas = mouse_move_deltas;
+        self
+    }
+
+    pub fn debounce(mut self, window: Duration) -> Self {
+        self.debounce = Some(window);
+        self
+    }
+
+    pub fn client_pos(mut self, client_pos: bool) -> Self {
+        self.client_pos = client_pos;
+        self
+    }
+
+    pub fn wheel_notches(mut self, wheel_notches: bool) -> Self {
+        self.wheel_notches = wheel_notches;
+        self
+    }
+
+    pub fn swap_buttons(mut self, swap_buttons: ButtonSwap) -> Self {
+        self.swap_buttons = swap_buttons;
+        self
+    }
+
+    pub fn callback_worker(mut self, capacity: usize) -> Self {
+        self.callback_worker = Some(capacity);
+        self
+    }
+
+    pub fn slow_callback_threshold(mut self, threshold: Option<Duration>) -> Self {
+        self.slow_callback_threshold = threshold;
+        self
+    }
+
+    pub fn reinstall_hooks(mut self, interval: Duration) -> Self {
+        self.reinstall_hooks = Some(interval);
+        self
+    }
+
+    pub fn only_when_foreground(mut self, target: ProcessMatch) -> Self {
+        self.only_when_foreground = Some(target);
+        self
+    }
+
+    pub fn overload_limit(mut self, limit: Option<u32>) -> Self {
+        self.overload_limit = limit;
+        self
+    }
+
+    pub fn dedup_mouse_moves(mut self, dedup_mouse_moves: bool) -> Self {
+        self.dedup_mouse_moves = dedup_mouse_moves;
+        self
+    }
+
+    pub fn block_mouse_move(mut self, block_mouse_move: bool) -> Self {
+        self.block_mouse_move = block_mouse_move;
+        self
+    }
+
+    pub fn target_thread(mut self, thread_id: u32) -> Self {
+        self.target_thread = Some(thread_id);
+        self
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{sync::Mutex, MouseButton, CONFIG};
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Source {
+    Key(u32),
+    Button(MouseButton),
+}
+
+#[derive(Default)]
+struct State {
+    //每个按键上一次有效的按下时间，以及之后是否已经抬起
+    last_down: HashMap<Source, (u32, bool)>,
+    //被判定为抖动的按下事件，对应的抬起事件同样丢弃
+    bounced: HashSet<Source>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+pub(crate) fn reset() {
+    *STATE.lock() = State::default();
+}
+
+/// 判断按下/抬起事件是否为按键抖动，抖动的事件不派发也不传递给其他程序
+pub(crate) fn is_bounce(source: Source, down: bool, time: u32) -> bool {
+    let window = match CONFIG.read().debounce {
+        Some(window) => window.as_millis() as u32,
+        None => return false,
+    };
+    let mut state = STATE.lock();
+    if !down {
+        if state.bounced.remove(&source) {
+            return true;
+        }
+        if let Some((_, released)) = state.last_down.get_mut(&source) {
+            *released = true;
+        }
+        return false;
+    }
+    match state.last_down.get(&source) {
+        //按住不放时系统自动重复的按下事件不是抖动
+        Some((_, false)) => false,
+        Some(&(last, true)) if u32::wrapping_sub(time, last) < window => {
+            state.bounced.insert(source);
+            true
+        }
+        _ => {
+            //抖动后按住不放，窗口之后的重复按下有效，对应的抬起不能再丢弃
+            state.bounced.remove(&source);
+            state.last_down.insert(source, (time, false));
+            false
+        }
+    }
+}
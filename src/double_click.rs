@@ -0,0 +1,59 @@
+use crate::{sync::Mutex, MouseEvent, MouseInfo, Point, CONFIG};
+use once_cell::sync::Lazy;
+use windows::Win32::UI::{
+    Input::KeyboardAndMouse::GetDoubleClickTime,
+    WindowsAndMessaging::{GetSystemMetrics, SM_CXDOUBLECLK, SM_CYDOUBLECLK},
+};
+
+//上一次可能构成双击的按下事件：(双击事件, 位置, 时间)
+static LAST_DOWN: Lazy<Mutex<Option<(MouseEvent, Point, u32)>>> = Lazy::new(|| Mutex::new(None));
+
+pub(crate) fn reset() {
+    LAST_DOWN.lock().take();
+}
+
+/// 根据按下事件判断是否构成双击，返回需要额外派发的双击事件
+pub(crate) fn detect(event: MouseEvent, info: &MouseInfo) -> Option<MouseEvent> {
+    let double_click = match event {
+        MouseEvent::MouseLeftButtonDown => MouseEvent::MouseLeftDoubleClick,
+        MouseEvent::MouseRightButtonDown => MouseEvent::MouseRightDoubleClick,
+        MouseEvent::MouseMiddleButtonDown => MouseEvent::MouseMiddleDoubleClick,
+        _ => return None,
+    };
+    let (time, (dx, dy)) = {
+        let config = CONFIG.read();
+        if !config.double_clicks {
+            return None;
+        }
+        let time = match config.double_click_time {
+            Some(time) => time.as_millis() as u32,
+            None => unsafe { GetDoubleClickTime() },
+        };
+        let distance = config.double_click_distance.unwrap_or_else(|| unsafe {
+            //系统的双击范围是以第一次点击为中心的矩形
+            (
+                GetSystemMetrics(SM_CXDOUBLECLK) / 2,
+                GetSystemMetrics(SM_CYDOUBLECLK) / 2,
+            )
+        });
+        (time, distance)
+    };
+    let mut last = LAST_DOWN.lock();
+    let is_double = match last.as_ref() {
+        Some((last_event, point, last_time)) => {
+            *last_event == double_click
+                && u32::wrapping_sub(info.time, *last_time) <= time
+                && (info.point.x - point.x).abs() <= dx
+                && (info.point.y - point.y).abs() <= dy
+        }
+        None => false,
+    };
+    if is_double {
+        //第三次点击重新开始计算，不会连续产生两次双击
+        last.take();
+        Some(double_click)
+    } else {
+        last.replace((double_click, info.point.clone(), info.time));
+        None
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{call_guarded, chord::is_part_of, sync::Mutex, Result};
+use once_cell::sync::Lazy;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+type DoubleTapAction = Arc<Mutex<Box<dyn Fn() + Send>>>;
+
+/// 双击按键的默认时间窗口
+pub const DEFAULT_DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DoubleTapId(u64);
+
+#[derive(Clone, Copy)]
+enum Stage {
+    Idle,
+    //第一次按下的时间
+    Pressed(u32),
+    Released(u32),
+}
+
+struct DoubleTap {
+    id: DoubleTapId,
+    vk: u32,
+    window: u32,
+    stage: Stage,
+    action: DoubleTapAction,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static TAPS: Lazy<Mutex<Vec<DoubleTap>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 注册双击按键，`vk`在`window`内连续按下两次且中间没有按其他键时执行`action`
+///
+/// 常用于单独的修饰键，例如双击Shift；`vk::SHIFT`等不区分左右的键码匹配任意一侧。
+/// 按住不放产生的重复按下不计入次数，`window`一般使用`DEFAULT_DOUBLE_TAP_WINDOW`。
+pub fn register_double_tap(
+    vk: u32,
+    window: Duration,
+    action: impl Fn() + Send + 'static,
+) -> Result<DoubleTapId> {
+    let id = DoubleTapId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    TAPS.lock().push(DoubleTap {
+        id,
+        vk,
+        window: window.as_millis().min(u32::MAX as u128) as u32,
+        stage: Stage::Idle,
+        action: Arc::new(Mutex::new(Box::new(action))),
+    });
+    Ok(id)
+}
+
+pub fn unregister_double_tap(id: DoubleTapId) -> Result<()> {
+    TAPS.lock().retain(|tap| tap.id != id);
+    Ok(())
+}
+
+pub(crate) fn reset() {
+    for tap in TAPS.lock().iter_mut() {
+        tap.stage = Stage::Idle;
+    }
+}
+
+/// 在键盘钩子中对每个按键事件调用
+pub(crate) fn process_key(vk: u32, down: bool, is_repeat: bool, time: u32) {
+    if is_repeat {
+        return;
+    }
+    //先取出要执行的动作再释放锁，动作中可以注册/注销双击按键
+    let action = {
+        let mut taps = TAPS.lock();
+        let mut action = None;
+        for tap in taps.iter_mut() {
+            if !is_part_of(tap.vk, vk) {
+                //中间按了其他键
+                if down {
+                    tap.stage = Stage::Idle;
+                }
+                continue;
+            }
+            tap.stage = match (tap.stage, down) {
+                (Stage::Released(first), true) if u32::wrapping_sub(time, first) <= tap.window => {
+                    action.get_or_insert_with(|| tap.action.clone());
+                    Stage::Idle
+                }
+                (Stage::Pressed(first), false) => Stage::Released(first),
+                (_, true) => Stage::Pressed(time),
+                (stage, false) => stage,
+            };
+        }
+        match action {
+            Some(action) => action,
+            None => return,
+        }
+    };
+    call_guarded(&*action.lock());
+}
@@ -0,0 +1,85 @@
+use crate::{sync::Mutex, ButtonAction, MouseButton, MouseEvent, MouseInfo, Point, CONFIG};
+use once_cell::sync::Lazy;
+use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_CXDRAG, SM_CYDRAG};
+
+/// 拖动事件的附加信息，当前位置为`MouseInfo::point`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DragInfo {
+    /// 按住拖动的按键
+    pub button: MouseButton,
+    /// 按下时的位置
+    pub start: Point,
+}
+
+struct DragState {
+    button: MouseButton,
+    start: Point,
+    dragging: bool,
+}
+
+static STATE: Lazy<Mutex<Option<DragState>>> = Lazy::new(|| Mutex::new(None));
+
+pub(crate) fn reset() {
+    STATE.lock().take();
+}
+
+/// 根据按键和移动事件跟踪拖动状态，返回需要额外派发的拖动事件
+pub(crate) fn detect(event: MouseEvent, info: &MouseInfo) -> Option<(MouseEvent, DragInfo)> {
+    let (dx, dy) = {
+        let config = CONFIG.read();
+        if !config.drags {
+            return None;
+        }
+        config.drag_threshold.unwrap_or_else(|| unsafe {
+            (GetSystemMetrics(SM_CXDRAG), GetSystemMetrics(SM_CYDRAG))
+        })
+    };
+    let mut state = STATE.lock();
+    let drag_event = match (event.button(), state.as_mut()) {
+        //同时按下多个按键时只跟踪第一个
+        (Some((button, ButtonAction::Down)), None) => {
+            state.replace(DragState {
+                button,
+                start: info.point.clone(),
+                dragging: false,
+            });
+            return None;
+        }
+        (Some((button, ButtonAction::Up)), Some(drag)) if button == drag.button => {
+            let drag = state.take()?;
+            if !drag.dragging {
+                return None;
+            }
+            return Some((
+                MouseEvent::DragEnd,
+                DragInfo {
+                    button: drag.button,
+                    start: drag.start,
+                },
+            ));
+        }
+        (None, Some(drag)) if event == MouseEvent::MouseMove => {
+            if drag.dragging {
+                MouseEvent::DragMove
+            } else if (info.point.x - drag.start.x).abs() > dx
+                || (info.point.y - drag.start.y).abs() > dy
+            {
+                //移动距离超过阈值才开始拖动，避免单击时的抖动
+                drag.dragging = true;
+                MouseEvent::DragStart
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    };
+    let drag = state.as_ref()?;
+    Some((
+        drag_event,
+        DragInfo {
+            button: drag.button,
+            start: drag.start.clone(),
+        },
+    ))
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, KbdmsError>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum KbdmsError {
+    /// SetWindowsHookExW失败
+    HookInstall(windows::core::Error),
+    /// 该类型的钩子已经安装
+    AlreadyHooked,
+    /// 钩子没有运行
+    NotRunning,
+    /// 钩子已经在运行
+    AlreadyRunning,
+    /// 钩子线程panic
+    ThreadPanicked,
+    /// SendInput失败
+    SendInput(windows::core::Error),
+    /// SendInput只插入了部分事件，例如被UIPI拦截或输入中途被打断
+    PartialInput { sent: u32, total: u32 },
+    /// 回放速度必须是大于0的有限数
+    InvalidSpeed(f32),
+    /// GetCursorPos失败，例如当前桌面不是输入桌面
+    CursorPos(windows::core::Error),
+    /// 创建`HookConfig::callback_worker`的回调工作线程失败
+    WorkerSpawn(std::io::ErrorKind),
+}
+
+impl KbdmsError {
+    /// Win32错误码(GetLastError)
+    pub fn win32_code(&self) -> Option<u32> {
+        match self {
+            KbdmsError::HookInstall(err)
+            | KbdmsError::SendInput(err)
+            | KbdmsError::CursorPos(err) => {
+                //windows::core::Error保存的是HRESULT_FROM_WIN32(GetLastError())
+                let hresult = err.code().0 as u32;
+                if hresult & 0xFFFF0000 == 0x80070000 {
+                    Some(hresult & 0xFFFF)
+                } else {
+                    Some(hresult)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KbdmsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KbdmsError::HookInstall(err) => write!(
+                f,
+                "SetWindowsHookExW failed ({}): low-level hooks need a running message loop on \
+                 the installing thread, and hooking elevated processes requires running elevated",
+                err
+            ),
+            KbdmsError::AlreadyHooked => write!(f, "hook is already installed"),
+            KbdmsError::NotRunning => write!(f, "hook is not running"),
+            KbdmsError::AlreadyRunning => write!(f, "hook is already running"),
+            KbdmsError::ThreadPanicked => write!(f, "hook thread panicked"),
+            KbdmsError::SendInput(err) => write!(f, "SendInput failed ({})", err),
+            KbdmsError::PartialInput { sent, total } => {
+                write!(f, "SendInput inserted only {} of {} events", sent, total)
+            }
+            KbdmsError::InvalidSpeed(speed) => write!(f, "invalid replay speed: {}", speed),
+            KbdmsError::CursorPos(err) => write!(f, "GetCursorPos failed ({})", err),
+            KbdmsError::WorkerSpawn(kind) => {
+                write!(f, "failed to spawn callback worker thread ({})", kind)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KbdmsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KbdmsError::HookInstall(err)
+            | KbdmsError::SendInput(err)
+            | KbdmsError::CursorPos(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{set_hook_callback_boxed, Dispatch, Event, KeyEvent, MouseEvent, MouseInfo, Result};
+use std::time::Duration;
+
+/// 按事件类型分别处理的事件处理器，未实现的方法默认不处理并放行事件
+pub trait EventHandler {
+    fn on_key(&mut self, _event: KeyEvent) -> Dispatch {
+        Dispatch::Pass
+    }
+
+    /// 鼠标位置为`info.point`
+    fn on_mouse(&mut self, _event: MouseEvent, _info: MouseInfo) -> Dispatch {
+        Dispatch::Pass
+    }
+
+    /// 需要开启`HookConfig::lifecycle_events`
+    fn on_hook_started(&mut self) {}
+
+    /// 需要开启`HookConfig::lifecycle_events`
+    fn on_hook_stopped(&mut self) {}
+
+    /// 需要开启`HookConfig::reinstall_hooks`
+    fn on_hook_reinstalled(&mut self) {}
+
+    /// 一次事件的处理时间超过`HookConfig::slow_callback_threshold`
+    fn on_callback_slow(&mut self, _duration: Duration) {}
+
+    /// 过载时丢弃了`dropped`个移动、滚轮事件
+    fn on_overload(&mut self, _dropped: u64) {}
+}
+
+/// 设置事件处理器，与`set_hook_callback`共用同一个回调位置，后设置的会覆盖之前的
+pub fn set_handler(mut handler: Box<dyn EventHandler + Send>) -> Result<()> {
+    set_hook_callback_boxed(Box::new(move |event| match event {
+        Event::KeyEvent(event) => handler.on_key(event),
+        Event::MouseEvent((event, info)) => handler.on_mouse(event, info),
+        Event::HookStarted => {
+            handler.on_hook_started();
+            Dispatch::Pass
+        }
+        Event::HookStopped => {
+            handler.on_hook_stopped();
+            Dispatch::Pass
+        }
+        Event::Overload { dropped } => {
+            handler.on_overload(dropped);
+            Dispatch::Pass
+        }
+        Event::HookReinstalled => {
+            handler.on_hook_reinstalled();
+            Dispatch::Pass
+        }
+        Event::CallbackSlow { duration } => {
+            handler.on_callback_slow(duration);
+            Dispatch::Pass
+        }
+    }))
+}
@@ -0,0 +1,41 @@
+use crate::{sync::Mutex, Event};
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+
+//(容量, 最近的事件)，容量为0表示不保存
+static RECENT: Lazy<Mutex<(usize, VecDeque<Event>)>> =
+    Lazy::new(|| Mutex::new((0, VecDeque::new())));
+
+pub(crate) fn set_capacity(capacity: usize) {
+    let mut recent = RECENT.lock();
+    let (cap, events) = &mut *recent;
+    *cap = capacity;
+    events.clear();
+    events.shrink_to(capacity);
+}
+
+pub(crate) fn push(event: &Event) {
+    let mut recent = RECENT.lock();
+    let (capacity, events) = &mut *recent;
+    if *capacity == 0 {
+        return;
+    }
+    if events.len() == *capacity {
+        events.pop_front();
+    }
+    events.push_back(event.clone());
+}
+
+/// 最近派发的最多`n`个事件，按时间从早到晚排列
+///
+/// 需要通过`HookConfig::recent_events`设置保存的数量，未开启时返回空列表。
+/// 钩子停止后仍然可以读取，下次启动钩子时清空。
+pub fn recent_events(n: usize) -> Vec<Event> {
+    let recent = RECENT.lock();
+    let events = &recent.1;
+    events
+        .iter()
+        .skip(events.len().saturating_sub(n))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,113 @@
+use crate::{add_listener, remove_listener, spawn_hook, start_hook_async, stop_owned_hook};
+use crate::{Dispatch, Event, HookConfig, HookHandle, KbdmsError, ListenerId, Result};
+use std::sync::mpsc::sync_channel;
+
+/// 钩子句柄，拥有钩子线程和回调，drop时自动卸载钩子
+///
+/// `Hook`并不拥有独立的钩子状态：Windows低级钩子的回调函数没有上下文参数，HHOOK、停止标志、
+/// 配置和按键状态都是进程内全局的，`Hook`只是全局钩子的一个便捷入口。同一时间只能有一个`Hook`
+/// (或`start_hook`等)在运行，否则`start`返回`KbdmsError::AlreadyRunning`；`stop_hook`等全局函数
+/// 同样会停止`Hook`启动的钩子。回调以监听器的方式注册，不会覆盖`set_hook_callback`设置的回调，
+/// 钩子停止后才移除，可以收到`Event::HookStopped`。
+pub struct Hook {
+    hook_mouse: bool,
+    hook_keyboard: bool,
+    listener: Option<ListenerId>,
+    handle: Option<HookHandle>,
+}
+
+impl Hook {
+    pub fn new(hook_mouse: bool, hook_keyboard: bool) -> Self {
+        Hook {
+            hook_mouse,
+            hook_keyboard,
+            listener: None,
+            handle: None,
+        }
+    }
+
+    /// 在新线程中安装钩子并运行消息循环
+    ///
+    /// 等待钩子安装完成后返回，安装失败或钩子已经在运行时返回对应的错误，并移除已注册的回调。
+    pub fn start(
+        &mut self,
+        callback: impl FnMut(Event) -> Dispatch + Send + 'static,
+    ) -> Result<()> {
+        if self.handle.is_some() || crate::is_running() {
+            return Err(KbdmsError::AlreadyRunning);
+        }
+        //安装前注册监听器，回调可以收到HookStarted
+        let listener = add_listener(callback)?;
+        let (sender, receiver) = sync_channel(1);
+        let config = HookConfig::new()
+            .mouse(self.hook_mouse)
+            .keyboard(self.hook_keyboard);
+        let handle = spawn_hook(config, Some(sender));
+        if let Ok(Ok(())) = receiver.recv() {
+            self.listener = Some(listener);
+            self.handle = Some(handle);
+            return Ok(());
+        }
+        //安装失败或没有拿到运行标志时线程已经退出，返回线程的结果
+        let result = handle.join().map_err(|_| KbdmsError::ThreadPanicked);
+        remove_listener(listener)?;
+        result?
+    }
+
+    /// 卸载钩子，等待钩子线程退出并返回消息循环的结果
+    pub fn stop(&mut self) -> Result<()> {
+        let result = match self.handle.take() {
+            Some(handle) => stop_and_join(handle),
+            None => Ok(()),
+        };
+        //消息循环结束后再移除监听器，回调可以收到HookStopped
+        if let Some(id) = self.listener.take() {
+            remove_listener(id)?;
+        }
+        result
+    }
+}
+
+impl Drop for Hook {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// `start_guarded`返回的守卫，drop时调用`stop_hook`并等待钩子线程退出
+pub struct HookGuard {
+    handle: Option<HookHandle>,
+}
+
+impl HookGuard {
+    /// 提前停止钩子并返回消息循环的结果，重复调用或之后drop都是安全的
+    pub fn stop(&mut self) -> Result<()> {
+        let handle = match self.handle.take() {
+            Some(handle) => handle,
+            None => return Ok(()),
+        };
+        stop_and_join(handle)
+    }
+}
+
+impl Drop for HookGuard {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+/// 在新线程中启动钩子，返回的守卫离开作用域时自动停止钩子
+pub fn start_guarded(hook_mouse: bool, hook_keyboard: bool) -> HookGuard {
+    HookGuard {
+        handle: Some(start_hook_async(hook_mouse, hook_keyboard)),
+    }
+}
+
+/// 停止`start_hook_async`启动的钩子，等待钩子线程退出并返回消息循环的结果
+///
+/// 返回时钩子已经卸载、消息循环已经结束；句柄没有启动成功(例如`AlreadyRunning`)或已经退出时只返回其结果，
+/// 不会停掉其他正在运行的钩子。
+pub fn stop_and_join(handle: HookHandle) -> Result<()> {
+    stop_owned_hook(&handle)?;
+    handle.join().map_err(|_| KbdmsError::ThreadPanicked)?
+}
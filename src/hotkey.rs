@@ -0,0 +1,102 @@
+use crate::{call_guarded, sync::Mutex, Dispatch, Modifiers, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+type HotkeyAction = Arc<Mutex<Box<dyn Fn() + Send>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HotkeyId(u64);
+
+struct Hotkey {
+    id: HotkeyId,
+    modifiers: Modifiers,
+    vk: u32,
+    block: bool,
+    action: HotkeyAction,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static HOTKEYS: Lazy<Mutex<Vec<Hotkey>>> = Lazy::new(|| Mutex::new(Vec::new()));
+//被热键拦截了按下事件的按键，抬起时同样拦截
+static BLOCKED_KEYS: Lazy<Mutex<HashSet<u32>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// 注册全局热键，按下`modifiers`中的修饰键和`vk`时执行`action`，按键继续传递
+///
+/// 当前修饰键包含`modifiers`即视为匹配。多个热键同时匹配时，修饰键最多(最具体)的热键生效，
+/// 相同时先注册的生效。热键需要启动键盘钩子才会触发。
+pub fn register_hotkey(
+    modifiers: Modifiers,
+    vk: u32,
+    action: impl Fn() + Send + 'static,
+) -> Result<HotkeyId> {
+    add_hotkey(modifiers, vk, false, Box::new(action))
+}
+
+/// 与[`register_hotkey`]相同，但触发后拦截该按键，不再传递给其他程序
+pub fn register_hotkey_blocking(
+    modifiers: Modifiers,
+    vk: u32,
+    action: impl Fn() + Send + 'static,
+) -> Result<HotkeyId> {
+    add_hotkey(modifiers, vk, true, Box::new(action))
+}
+
+pub fn unregister_hotkey(id: HotkeyId) -> Result<()> {
+    HOTKEYS.lock().retain(|hotkey| hotkey.id != id);
+    Ok(())
+}
+
+fn add_hotkey(
+    modifiers: Modifiers,
+    vk: u32,
+    block: bool,
+    action: Box<dyn Fn() + Send>,
+) -> Result<HotkeyId> {
+    let id = HotkeyId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    HOTKEYS.lock().push(Hotkey {
+        id,
+        modifiers,
+        vk,
+        block,
+        action: Arc::new(Mutex::new(action)),
+    });
+    Ok(id)
+}
+
+/// 在键盘钩子中对每次按键抬起调用，热键拦截了按下事件时同样拦截抬起
+///
+/// 暂停或忽略的按键也要调用，否则记录一直保留，之后的抬起会被误拦截。
+pub(crate) fn release_key(vk: u32) -> Dispatch {
+    if BLOCKED_KEYS.lock().remove(&vk) {
+        Dispatch::Block
+    } else {
+        Dispatch::Pass
+    }
+}
+
+/// 在键盘钩子中对按键按下调用，执行匹配的热键并返回是否拦截该按键
+pub(crate) fn process_key(vk: u32, modifiers: Modifiers) -> Dispatch {
+    //先取出要执行的动作再释放锁，动作中可以注册/注销热键
+    let matched = HOTKEYS
+        .lock()
+        .iter()
+        .filter(|hotkey| hotkey.vk == vk && modifiers.contains(hotkey.modifiers))
+        //max_by_key在相等时返回最后一个，反向迭代使先注册的优先
+        .rev()
+        .max_by_key(|hotkey| hotkey.modifiers.bits().count_ones())
+        .map(|hotkey| (hotkey.block, hotkey.action.clone()));
+    let (block, action) = match matched {
+        Some(matched) => matched,
+        None => return Dispatch::Pass,
+    };
+    call_guarded(&*action.lock());
+    if !block {
+        return Dispatch::Pass;
+    }
+    BLOCKED_KEYS.lock().insert(vk);
+    Dispatch::Block
+}
@@ -0,0 +1,36 @@
+use crate::sync::Mutex;
+use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
+use windows::Win32::{
+    System::SystemInformation::GetTickCount,
+    UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+};
+
+//钩子最后一次收到键盘或鼠标事件的时间
+static LAST_INPUT: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+pub(crate) fn touch() {
+    LAST_INPUT.lock().replace(Instant::now());
+}
+
+/// 距离钩子最后一次收到键盘或鼠标事件的时间，钩子还没有收到过事件时返回None
+///
+/// 包括被过滤、没有派发给回调的事件和注入的事件；只统计已安装的钩子类型，
+/// 钩子收不到的输入(如其他桌面上的输入)不会计入，需要系统范围的结果时使用`system_idle_duration`。
+pub fn idle_duration() -> Option<Duration> {
+    LAST_INPUT.lock().map(|last| last.elapsed())
+}
+
+/// 通过GetLastInputInfo获取系统范围的空闲时间，不需要启动钩子
+pub fn system_idle_duration() -> Option<Duration> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    //GetTickCount约49.7天回绕一次
+    let elapsed = u32::wrapping_sub(unsafe { GetTickCount() }, info.dwTime);
+    Some(Duration::from_millis(elapsed as u64))
+}
@@ -0,0 +1,264 @@
+use crate::{
+    keys::is_extended_key, ButtonAction, Event, KbdmsError, KeyEvent, MouseButton, MouseEvent,
+    Point, Result, CONFIG,
+};
+use windows::Win32::{
+    Foundation::POINT,
+    UI::{
+        Input::KeyboardAndMouse::{
+            MapVirtualKeyW, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT,
+            KEYBD_EVENT_FLAGS, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+            MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_HWHEEL, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+            MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, MOUSEEVENTF_MOVE, MOUSEEVENTF_RIGHTDOWN,
+            MOUSEEVENTF_RIGHTUP, MOUSEEVENTF_VIRTUALDESK, MOUSEEVENTF_WHEEL, MOUSEEVENTF_XDOWN,
+            MOUSEEVENTF_XUP, MOUSEINPUT, MOUSE_EVENT_FLAGS, VIRTUAL_KEY,
+        },
+        WindowsAndMessaging::{
+            GetCursorPos, GetSystemMetrics, MAPVK_VK_TO_VSC, SM_CXVIRTUALSCREEN,
+            SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN, XBUTTON1, XBUTTON2,
+        },
+    },
+};
+
+/// 本库注入的事件在dwExtraInfo中携带的标记，钩子可以据此区分本库与其他工具注入的事件
+pub const INJECTED_EXTRA_INFO: usize = 0x4B42_4D53; //"KBMS"
+
+/// 模拟按下(`down`为true)或抬起按键，返回成功插入的事件数量
+///
+/// SendInput没有插入任何事件(例如被UIPI拦截)时返回`KbdmsError::SendInput`，不会静默成功。
+/// 方向键、Insert等扩展键会自动设置KEYEVENTF_EXTENDEDKEY。
+pub fn send_key(vk: u32, down: bool) -> Result<u32> {
+    send_inputs(&[keyboard_input(vk, scan_code(vk), down)])
+}
+
+/// 模拟按下并抬起按键，返回成功插入的事件数量
+pub fn tap_key(vk: u32) -> Result<u32> {
+    let scan_code = scan_code(vk);
+    send_inputs(&[
+        keyboard_input(vk, scan_code, true),
+        keyboard_input(vk, scan_code, false),
+    ])
+}
+
+/// 通过KEYEVENTF_UNICODE输入文本，与键盘布局无关
+///
+/// 每个UTF-16编码单元依次发送按下和抬起事件，emoji等代理对按顺序发送两个编码单元。
+/// 只有部分事件被插入时返回`KbdmsError::PartialInput`。
+pub fn type_text(text: &str) -> Result<u32> {
+    let inputs: Vec<INPUT> = text
+        .encode_utf16()
+        .flat_map(|unit| [unicode_input(unit, true), unicode_input(unit, false)])
+        .collect();
+    let sent = send_inputs(&inputs)?;
+    if (sent as usize) < inputs.len() {
+        return Err(KbdmsError::PartialInput {
+            sent,
+            total: inputs.len() as u32,
+        });
+    }
+    Ok(sent)
+}
+
+/// 把光标移动到屏幕坐标(x, y)，支持多显示器的虚拟桌面坐标
+pub fn move_mouse_to(x: i32, y: i32) -> Result<u32> {
+    send_inputs(&[mouse_input_at(&Point { x, y }, 0, MOUSE_EVENT_FLAGS(0))])
+}
+
+/// 当前光标的屏幕坐标，不需要启动钩子
+pub fn cursor_position() -> Result<Point> {
+    let mut pos = POINT::default();
+    if !unsafe { GetCursorPos(&mut pos) }.as_bool() {
+        return Err(KbdmsError::CursorPos(windows::core::Error::from_win32()));
+    }
+    Ok(Point { x: pos.x, y: pos.y })
+}
+
+/// 在当前光标位置单击鼠标按键
+///
+/// 默认注入物理按键，`HookConfig::swap_buttons`设置的对应方式在钩子停止后仍然生效。
+pub fn click(button: MouseButton) -> Result<u32> {
+    let (mouse_data, down) = button_input(button, ButtonAction::Down);
+    let (_, up) = button_input(button, ButtonAction::Up);
+    send_inputs(&[
+        mouse_input(0, 0, mouse_data, down),
+        mouse_input(0, 0, mouse_data, up),
+    ])
+}
+
+/// 滚动垂直滚轮，`delta`为WHEEL_DELTA(120)的倍数，正数向前滚动
+pub fn scroll(delta: i32) -> Result<u32> {
+    send_inputs(&[mouse_input(0, 0, delta, MOUSEEVENTF_WHEEL)])
+}
+
+/// 鼠标按键动作对应的mouseData和标志
+fn button_input(button: MouseButton, action: ButtonAction) -> (i32, MOUSE_EVENT_FLAGS) {
+    //与钩子上报的按键使用同样的左右键对应方式
+    let swapped = CONFIG.read().swap_buttons.is_swapped();
+    let button = match button {
+        MouseButton::Left if swapped => MouseButton::Right,
+        MouseButton::Right if swapped => MouseButton::Left,
+        button => button,
+    };
+    let (mouse_data, down, up) = match button {
+        MouseButton::Left => (0, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP),
+        MouseButton::Right => (0, MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP),
+        MouseButton::Middle => (0, MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP),
+        MouseButton::X1 => (XBUTTON1.0 as i32, MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP),
+        MouseButton::X2 => (XBUTTON2.0 as i32, MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP),
+    };
+    match action {
+        ButtonAction::Down => (mouse_data, down),
+        ButtonAction::Up => (mouse_data, up),
+    }
+}
+
+pub(crate) fn scan_code(vk: u32) -> u32 {
+    unsafe { MapVirtualKeyW(vk, MAPVK_VK_TO_VSC) }
+}
+
+pub(crate) fn keyboard_input(vk: u32, scan_code: u32, down: bool) -> INPUT {
+    tagged_keyboard_input(vk, scan_code, down, INJECTED_EXTRA_INFO)
+}
+
+/// 与`keyboard_input`相同，dwExtraInfo使用`extra_info`
+pub(crate) fn tagged_keyboard_input(
+    vk: u32,
+    scan_code: u32,
+    down: bool,
+    extra_info: usize,
+) -> INPUT {
+    let mut flags = KEYBD_EVENT_FLAGS(0);
+    if !down {
+        flags |= KEYEVENTF_KEYUP;
+    }
+    if is_extended_key(vk) {
+        flags |= KEYEVENTF_EXTENDEDKEY;
+    }
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(vk as u16),
+                wScan: scan_code as u16,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: extra_info,
+            },
+        },
+    }
+}
+
+fn unicode_input(unit: u16, down: bool) -> INPUT {
+    let mut flags = KEYEVENTF_UNICODE;
+    if !down {
+        flags |= KEYEVENTF_KEYUP;
+    }
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(0),
+                wScan: unit,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: INJECTED_EXTRA_INFO,
+            },
+        },
+    }
+}
+
+pub(crate) fn mouse_input(dx: i32, dy: i32, mouse_data: i32, flags: MOUSE_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_MOUSE,
+        Anonymous: INPUT_0 {
+            mi: MOUSEINPUT {
+                dx,
+                dy,
+                mouseData: mouse_data,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: INJECTED_EXTRA_INFO,
+            },
+        },
+    }
+}
+
+/// 虚拟桌面(所有显示器)的左上角坐标和宽高，主显示器左侧或上方有显示器时左上角为负数
+pub(crate) fn virtual_desktop_bounds() -> (i32, i32, i32, i32) {
+    unsafe {
+        (
+            GetSystemMetrics(SM_XVIRTUALSCREEN),
+            GetSystemMetrics(SM_YVIRTUALSCREEN),
+            GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        )
+    }
+}
+
+/// 把屏幕坐标换算为SendInput绝对坐标(整个虚拟桌面映射到0..65535)
+pub(crate) fn normalize_point(point: &Point) -> (i32, i32) {
+    let (left, top, width, height) = virtual_desktop_bounds();
+    let scale = |v: i32, origin: i32, size: i32| {
+        if size <= 1 {
+            return 0;
+        }
+        ((v - origin) as i64 * 65535 / (size - 1) as i64).clamp(0, 65535) as i32
+    };
+    (scale(point.x, left, width), scale(point.y, top, height))
+}
+
+/// 在指定位置执行鼠标动作
+pub(crate) fn mouse_input_at(point: &Point, mouse_data: i32, flags: MOUSE_EVENT_FLAGS) -> INPUT {
+    let (dx, dy) = normalize_point(point);
+    mouse_input(
+        dx,
+        dy,
+        mouse_data,
+        flags | MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK,
+    )
+}
+
+/// 把钩子收到的事件转换为可以重新注入的INPUT
+pub(crate) fn event_input(event: &Event) -> Option<INPUT> {
+    match event {
+        Event::KeyEvent(KeyEvent::KeyPress(info)) => {
+            Some(keyboard_input(info.vk_code, info.scan_code, true))
+        }
+        Event::KeyEvent(KeyEvent::KeyUp(info)) => {
+            Some(keyboard_input(info.vk_code, info.scan_code, false))
+        }
+        Event::MouseEvent((mouse_event, info)) => {
+            let (mouse_data, flags) = match mouse_event {
+                MouseEvent::MouseMove => (0, MOUSE_EVENT_FLAGS(0)),
+                MouseEvent::MouseWheel => (info.wheel_delta as i32, MOUSEEVENTF_WHEEL),
+                MouseEvent::MouseHWheel => (info.wheel_delta as i32, MOUSEEVENTF_HWHEEL),
+                event => {
+                    //合成的双击、拖动、整格滚轮事件没有对应的输入，原始事件已经单独注入
+                    let (button, action) = event.button()?;
+                    button_input(button, action)
+                }
+            };
+            Some(mouse_input_at(&info.point, mouse_data, flags))
+        }
+        Event::HookStarted
+        | Event::HookStopped
+        | Event::CallbackSlow { .. }
+        | Event::HookReinstalled
+        | Event::Overload { .. } => None,
+    }
+}
+
+/// 调用SendInput，返回成功插入的事件数量
+///
+/// 插入的数量可能少于请求的数量；一个都没有插入(例如被UIPI或其他低级钩子拦截)时
+/// 返回带GetLastError的`KbdmsError::SendInput`，可以通过`KbdmsError::win32_code`读取错误码。
+pub(crate) fn send_inputs(inputs: &[INPUT]) -> Result<u32> {
+    if inputs.is_empty() {
+        return Ok(0);
+    }
+    let sent = unsafe { SendInput(inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent == 0 {
+        return Err(KbdmsError::SendInput(windows::core::Error::from_win32()));
+    }
+    Ok(sent)
+}
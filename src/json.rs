@@ -0,0 +1,73 @@
+use crate::{Event, KeyEvent, MouseEvent};
+
+impl Event {
+    /// 转换为单行JSON，不依赖serde，字段名保持稳定
+    ///
+    /// - 键盘：`{"type":"key_press","vk":65,"scan":30,"time":0,"injected":false}`，
+    ///   抬起时`type`为`key_up`
+    /// - 鼠标：`{"type":"mouse","event":"left_down","x":10,"y":20,"delta":0,"time":0,"injected":false}`，
+    ///   `delta`为滚轮滚动量，`event`的取值见[`MouseEvent`]各变体，如`move`、`wheel`、`x1_up`、
+    ///   `left_double_click`、`drag_start`
+    /// - 生命周期：`{"type":"hook_started"}`/`{"type":"hook_stopped"}`/`{"type":"hook_reinstalled"}`
+    /// - 回调超时：`{"type":"callback_slow","duration_ms":250}`
+    /// - 过载：`{"type":"overload","dropped":120}`
+    pub fn to_json(&self) -> String {
+        match self {
+            Event::KeyEvent(event) => {
+                let (kind, info) = match event {
+                    KeyEvent::KeyPress(info) => ("key_press", info),
+                    KeyEvent::KeyUp(info) => ("key_up", info),
+                };
+                format!(
+                    r#"{{"type":"{}","vk":{},"scan":{},"time":{},"injected":{}}}"#,
+                    kind, info.vk_code, info.scan_code, info.time, info.injected
+                )
+            }
+            Event::MouseEvent((event, info)) => format!(
+                r#"{{"type":"mouse","event":"{}","x":{},"y":{},"delta":{},"time":{},"injected":{}}}"#,
+                mouse_event_name(*event),
+                info.point.x,
+                info.point.y,
+                info.wheel_delta,
+                info.time,
+                info.injected
+            ),
+            Event::HookStarted => r#"{"type":"hook_started"}"#.to_string(),
+            Event::HookStopped => r#"{"type":"hook_stopped"}"#.to_string(),
+            Event::HookReinstalled => r#"{"type":"hook_reinstalled"}"#.to_string(),
+            Event::Overload { dropped } => {
+                format!(r#"{{"type":"overload","dropped":{}}}"#, dropped)
+            }
+            Event::CallbackSlow { duration } => format!(
+                r#"{{"type":"callback_slow","duration_ms":{}}}"#,
+                duration.as_millis()
+            ),
+        }
+    }
+}
+
+fn mouse_event_name(event: MouseEvent) -> &'static str {
+    match event {
+        MouseEvent::MouseMove => "move",
+        MouseEvent::MouseLeftButtonDown => "left_down",
+        MouseEvent::MouseLeftButtonUp => "left_up",
+        MouseEvent::MouseRightButtonDown => "right_down",
+        MouseEvent::MouseRightButtonUp => "right_up",
+        MouseEvent::MouseMiddleButtonDown => "middle_down",
+        MouseEvent::MouseMiddleButtonUp => "middle_up",
+        MouseEvent::MouseXButton1Down => "x1_down",
+        MouseEvent::MouseXButton1Up => "x1_up",
+        MouseEvent::MouseXButton2Down => "x2_down",
+        MouseEvent::MouseXButton2Up => "x2_up",
+        MouseEvent::MouseLeftDoubleClick => "left_double_click",
+        MouseEvent::MouseRightDoubleClick => "right_double_click",
+        MouseEvent::MouseMiddleDoubleClick => "middle_double_click",
+        MouseEvent::MouseWheel => "wheel",
+        MouseEvent::MouseHWheel => "hwheel",
+        MouseEvent::MouseWheelNotch => "wheel_notch",
+        MouseEvent::MouseHWheelNotch => "hwheel_notch",
+        MouseEvent::DragStart => "drag_start",
+        MouseEvent::DragMove => "drag_move",
+        MouseEvent::DragEnd => "drag_end",
+    }
+}
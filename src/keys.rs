@@ -0,0 +1,338 @@
+use crate::vk;
+use bitflags::bitflags;
+use windows::Win32::UI::{
+    Input::KeyboardAndMouse::{
+        GetAsyncKeyState, GetKeyNameTextW, GetKeyState, GetKeyboardLayout, MapVirtualKeyW,
+        ToUnicodeEx,
+    },
+    WindowsAndMessaging::{
+        GetForegroundWindow, GetWindowThreadProcessId, LLKHF_EXTENDED, MAPVK_VK_TO_VSC,
+    },
+};
+
+bitflags! {
+    /// 修饰键状态
+    ///
+    /// `SHIFT`/`CONTROL`/`ALT`/`WIN` 在任意一侧按下时置位，`L*`/`R*` 表示具体哪一侧。
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Modifiers: u32 {
+        const SHIFT = 0x1;
+        const CONTROL = 0x2;
+        const ALT = 0x4;
+        const WIN = 0x8;
+        const LSHIFT = 0x10;
+        const RSHIFT = 0x20;
+        const LCONTROL = 0x40;
+        const RCONTROL = 0x80;
+        const LALT = 0x100;
+        const RALT = 0x200;
+        const LWIN = 0x400;
+        const RWIN = 0x800;
+    }
+}
+
+impl Modifiers {
+    /// 按下或抬起修饰键后的新状态，非修饰键返回原状态
+    pub(crate) fn update(self, vk: u32, down: bool) -> Modifiers {
+        let side = match vk {
+            //不区分左右的VK_SHIFT/VK_CONTROL/VK_MENU按左侧处理
+            0x10 | 0xA0 => Modifiers::LSHIFT,
+            0xA1 => Modifiers::RSHIFT,
+            0x11 | 0xA2 => Modifiers::LCONTROL,
+            0xA3 => Modifiers::RCONTROL,
+            0x12 | 0xA4 => Modifiers::LALT,
+            0xA5 => Modifiers::RALT,
+            0x5B => Modifiers::LWIN,
+            0x5C => Modifiers::RWIN,
+            _ => return self,
+        };
+        let mut state = self;
+        state.set(side, down);
+        let pairs = [
+            (Modifiers::SHIFT, Modifiers::LSHIFT | Modifiers::RSHIFT),
+            (
+                Modifiers::CONTROL,
+                Modifiers::LCONTROL | Modifiers::RCONTROL,
+            ),
+            (Modifiers::ALT, Modifiers::LALT | Modifiers::RALT),
+            (Modifiers::WIN, Modifiers::LWIN | Modifiers::RWIN),
+        ];
+        for (any, sides) in pairs {
+            state.set(any, state.intersects(sides));
+        }
+        state
+    }
+}
+
+/// 把不区分左右的VK_SHIFT/VK_CONTROL/VK_MENU转换为具体哪一侧的虚拟键码
+///
+/// 右Shift的扫描码为0x36，右Ctrl/右Alt带有扩展键标志(LLKHF_EXTENDED)，其他按键原样返回。
+pub fn resolve_side(vk: u32, scan_code: u32, flags: u32) -> u32 {
+    let extended = flags & LLKHF_EXTENDED.0 != 0;
+    match vk {
+        //VK_SHIFT => VK_RSHIFT/VK_LSHIFT
+        0x10 if scan_code == 0x36 => 0xA1,
+        0x10 => 0xA0,
+        //VK_CONTROL => VK_RCONTROL/VK_LCONTROL
+        0x11 if extended => 0xA3,
+        0x11 => 0xA2,
+        //VK_MENU => VK_RMENU/VK_LMENU
+        0x12 if extended => 0xA5,
+        0x12 => 0xA4,
+        _ => vk,
+    }
+}
+
+/// 是否为音量、媒体播放控制键(`vk::VOLUME_MUTE`..=`vk::MEDIA_PLAY_PAUSE`)
+///
+/// 键盘上的媒体键会以普通的按下/抬起事件经过低级键盘钩子，键码为`vk::VOLUME_UP`等。
+/// 部分设备(遥控器、鼠标上的多媒体键等)不产生按键，而是直接向前台窗口发送`WM_APPCOMMAND`，
+/// 低级钩子收不到这类输入，需要全局WH_SHELL钩子(HSHELL_APPCOMMAND)，
+/// 而全局WH_SHELL钩子必须放在DLL中注入其他进程，本库不支持。
+pub fn is_media_key(vk: u32) -> bool {
+    (vk::VOLUME_MUTE..=vk::MEDIA_PLAY_PAUSE).contains(&vk)
+}
+
+/// 根据扫描码和扩展键标志判断按键是否在小键盘上
+///
+/// 关闭NumLock时小键盘数字键报告的是VK_HOME、VK_LEFT等虚拟键码，与独立的方向键相同，
+/// 两者的区别是独立按键带有扩展键标志；小键盘Enter和除号则带有扩展键标志。
+pub fn is_numpad(scan_code: u32, flags: u32) -> bool {
+    let extended = flags & LLKHF_EXTENDED.0 != 0;
+    match scan_code {
+        //7 8 9 - 4 5 6 + 1 2 3 0 .
+        0x47..=0x53 => !extended,
+        //乘号，带扩展键标志时为PrintScreen
+        0x37 => !extended,
+        //除号、Enter、NumLock
+        0x35 | 0x1C | 0x45 => extended,
+        _ => false,
+    }
+}
+
+/// 获取虚拟键码对应的按键名称
+///
+/// 优先使用GetKeyNameTextW获取当前键盘布局下的名称，失败时使用内置的英文名称表。
+pub fn key_name(vk: u32) -> Option<String> {
+    system_key_name(vk).or_else(|| static_key_name(vk).map(String::from))
+}
+
+fn system_key_name(vk: u32) -> Option<String> {
+    let scan_code = unsafe { MapVirtualKeyW(vk, MAPVK_VK_TO_VSC) };
+    if scan_code == 0 {
+        return None;
+    }
+    //GetKeyNameTextW的参数与WM_KEYDOWN的lParam格式相同: 16-23位为扫描码，24位为扩展键标志
+    let mut lparam = (scan_code << 16) as i32;
+    if is_extended_key(vk) {
+        lparam |= 1 << 24;
+    }
+    let mut buf = [0u16; 64];
+    let len = unsafe { GetKeyNameTextW(lparam, &mut buf) };
+    if len <= 0 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buf[..len as usize]))
+}
+
+/// 锁定键的开关状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockStates {
+    pub caps: bool,
+    pub num: bool,
+    pub scroll: bool,
+}
+
+/// 读取CapsLock、NumLock、ScrollLock的开关状态
+///
+/// GetKeyState返回值的最低位是开关状态，最高位才是按键是否按下。
+/// 读取的是调用线程消息队列中的状态，在钩子回调中不包含当前正在处理的按键。
+pub fn lock_states() -> LockStates {
+    let toggled = |vk: u32| unsafe { GetKeyState(vk as i32) & 1 != 0 };
+    LockStates {
+        caps: toggled(vk::CAPITAL),
+        num: toggled(vk::NUMLOCK),
+        scroll: toggled(vk::SCROLL),
+    }
+}
+
+/// 通过GetAsyncKeyState查询按键当前是否按下，不需要启动钩子
+///
+/// 鼠标按键使用`vk::LBUTTON`等键码。低级钩子回调执行时系统按键状态还没有更新，
+/// 在回调中得到的是当前事件之前的状态。
+pub fn is_key_down(vk: u32) -> bool {
+    //最高位为1表示按键按下
+    unsafe { GetAsyncKeyState(vk as i32) < 0 }
+}
+
+/// 按键自上一次调用GetAsyncKeyState以来是否被按下过
+///
+/// 最低位由系统中所有调用GetAsyncKeyState的程序共享，其他程序的调用也会清除它，
+/// 只适合粗略的轮询；需要可靠的按键事件时应使用钩子。
+pub fn was_key_pressed(vk: u32) -> bool {
+    unsafe { GetAsyncKeyState(vk as i32) & 1 != 0 }
+}
+
+/// 按前台窗口的键盘布局把按键转换为输入的字符
+///
+/// 按键状态由`modifiers`和当前的CapsLock状态构造，不读取系统的按键状态。
+/// 死键(如法语布局的`^`)返回None，并且不会影响系统中死键的组合状态。
+pub fn to_unicode(vk: u32, scan_code: u32, modifiers: Modifiers) -> Option<String> {
+    const KEYS: [(Modifiers, &[u32]); 9] = [
+        (Modifiers::SHIFT, &[vk::SHIFT]),
+        (Modifiers::LSHIFT, &[vk::LSHIFT]),
+        (Modifiers::RSHIFT, &[vk::RSHIFT]),
+        (Modifiers::CONTROL, &[vk::CONTROL]),
+        (Modifiers::LCONTROL, &[vk::LCONTROL]),
+        (Modifiers::RCONTROL, &[vk::RCONTROL]),
+        (Modifiers::ALT, &[vk::MENU]),
+        (Modifiers::LALT, &[vk::LMENU]),
+        //AltGr在布局中等同于Ctrl+Alt
+        (Modifiers::RALT, &[vk::RMENU, vk::MENU, vk::CONTROL]),
+    ];
+    let mut state = [0u8; 256];
+    for (modifier, vks) in KEYS {
+        if modifiers.contains(modifier) {
+            for &vk in vks {
+                state[vk as usize] = 0x80;
+            }
+        }
+    }
+    unsafe {
+        if lock_states().caps {
+            state[vk::CAPITAL as usize] = 0x01;
+        }
+        let thread_id = GetWindowThreadProcessId(GetForegroundWindow(), None);
+        let layout = GetKeyboardLayout(thread_id);
+        let mut buffer = [0u16; 8];
+        //wFlags的第2位：不修改内核中的键盘状态(死键缓冲)，Windows 10 1607开始支持
+        let len = ToUnicodeEx(vk, scan_code, &state, &mut buffer, 0x4, layout);
+        if len <= 0 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&buffer[..len as usize]))
+    }
+}
+
+/// 需要设置扩展键标志的虚拟键
+pub(crate) fn is_extended_key(vk: u32) -> bool {
+    matches!(
+        vk,
+        //PageUp/PageDown/End/Home/方向键
+        0x21..=0x28
+        //PrintScreen/Insert/Delete
+        | 0x2C..=0x2E
+        //左右Win键/Apps
+        | 0x5B..=0x5D
+        //小键盘除号
+        | 0x6F
+        //NumLock
+        | 0x90
+        //右Ctrl/右Alt
+        | 0xA3
+        | 0xA5
+        //浏览器、音量、媒体键
+        | 0xA6..=0xB7
+    )
+}
+
+fn static_key_name(vk: u32) -> Option<&'static str> {
+    const LETTERS: [&str; 26] = [
+        "A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R",
+        "S", "T", "U", "V", "W", "X", "Y", "Z",
+    ];
+    const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+    const NUMPAD: [&str; 10] = [
+        "NumPad0", "NumPad1", "NumPad2", "NumPad3", "NumPad4", "NumPad5", "NumPad6", "NumPad7",
+        "NumPad8", "NumPad9",
+    ];
+    const FUNCTION: [&str; 24] = [
+        "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "F13", "F14",
+        "F15", "F16", "F17", "F18", "F19", "F20", "F21", "F22", "F23", "F24",
+    ];
+    let name = match vk {
+        0x41..=0x5A => LETTERS[(vk - 0x41) as usize],
+        0x30..=0x39 => DIGITS[(vk - 0x30) as usize],
+        0x60..=0x69 => NUMPAD[(vk - 0x60) as usize],
+        0x70..=0x87 => FUNCTION[(vk - 0x70) as usize],
+        0x01 => "LeftButton",
+        0x02 => "RightButton",
+        0x03 => "Cancel",
+        0x04 => "MiddleButton",
+        0x05 => "XButton1",
+        0x06 => "XButton2",
+        0x08 => "Backspace",
+        0x09 => "Tab",
+        0x0C => "Clear",
+        0x0D => "Enter",
+        0x10 => "Shift",
+        0x11 => "Ctrl",
+        0x12 => "Alt",
+        0x13 => "Pause",
+        0x14 => "CapsLock",
+        0x1B => "Escape",
+        0x20 => "Space",
+        0x21 => "PageUp",
+        0x22 => "PageDown",
+        0x23 => "End",
+        0x24 => "Home",
+        0x25 => "Left",
+        0x26 => "Up",
+        0x27 => "Right",
+        0x28 => "Down",
+        0x2C => "PrintScreen",
+        0x2D => "Insert",
+        0x2E => "Delete",
+        0x5B => "LeftWin",
+        0x5C => "RightWin",
+        0x5D => "Apps",
+        0x5F => "Sleep",
+        0x6A => "Multiply",
+        0x6B => "Add",
+        0x6C => "Separator",
+        0x6D => "Subtract",
+        0x6E => "Decimal",
+        0x6F => "Divide",
+        0x90 => "NumLock",
+        0x91 => "ScrollLock",
+        0xA0 => "LeftShift",
+        0xA1 => "RightShift",
+        0xA2 => "LeftCtrl",
+        0xA3 => "RightCtrl",
+        0xA4 => "LeftAlt",
+        0xA5 => "RightAlt",
+        0xA6 => "BrowserBack",
+        0xA7 => "BrowserForward",
+        0xA8 => "BrowserRefresh",
+        0xA9 => "BrowserStop",
+        0xAA => "BrowserSearch",
+        0xAB => "BrowserFavorites",
+        0xAC => "BrowserHome",
+        0xAD => "VolumeMute",
+        0xAE => "VolumeDown",
+        0xAF => "VolumeUp",
+        0xB0 => "MediaNextTrack",
+        0xB1 => "MediaPrevTrack",
+        0xB2 => "MediaStop",
+        0xB3 => "MediaPlayPause",
+        0xB4 => "LaunchMail",
+        0xB5 => "LaunchMediaSelect",
+        0xB6 => "LaunchApp1",
+        0xB7 => "LaunchApp2",
+        0xBA => ";",
+        0xBB => "=",
+        0xBC => ",",
+        0xBD => "-",
+        0xBE => ".",
+        0xBF => "/",
+        0xC0 => "`",
+        0xDB => "[",
+        0xDC => "\\",
+        0xDD => "]",
+        0xDE => "'",
+        _ => return None,
+    };
+    Some(name)
+}
@@ -1,197 +1,1584 @@
-use anyhow::{anyhow, Result};
+//日志宏需要先于使用它们的模块声明
+#[macro_use]
+mod logging;
+mod block;
+mod chord;
+mod config;
+mod debounce;
+mod double_click;
+mod double_tap;
+mod drag;
+mod error;
+mod handler;
+mod history;
+mod hook;
+mod hotkey;
+mod idle;
+mod input;
+mod json;
+mod keys;
+mod listener;
+mod overload;
+mod record;
+mod remap;
+mod stats;
+#[cfg(feature = "tokio")]
+mod stream;
+mod suppress;
+mod sync;
+mod thread_hook;
+pub mod vk;
+mod watchdog;
+mod wheel;
+mod window;
+mod worker;
+
+pub use block::set_blocked_keys;
+pub use chord::{register_chord, unregister_chord, ChordId};
+pub use config::{ButtonSwap, HookConfig};
+pub use double_tap::{
+    register_double_tap, unregister_double_tap, DoubleTapId, DEFAULT_DOUBLE_TAP_WINDOW,
+};
+pub use drag::DragInfo;
+pub use error::{KbdmsError, Result};
+pub use handler::{set_handler, EventHandler};
+pub use history::recent_events;
+pub use hook::{start_guarded, stop_and_join, Hook, HookGuard};
+pub use hotkey::{register_hotkey, register_hotkey_blocking, unregister_hotkey, HotkeyId};
+pub use idle::{idle_duration, system_idle_duration};
+pub use input::{
+    click, cursor_position, move_mouse_to, scroll, send_key, tap_key, type_text,
+    INJECTED_EXTRA_INFO,
+};
+pub use keys::{
+    is_key_down, is_media_key, is_numpad, key_name, lock_states, resolve_side, to_unicode,
+    was_key_pressed, LockStates, Modifiers,
+};
+pub use listener::{add_listener, add_listener_with_mask, remove_listener, EventMask, ListenerId};
+pub use record::{replay, start_recording, stop_recording, RecordedEvent};
+pub use remap::{clear_remap, set_remap, REMAPPED_EXTRA_INFO};
+pub use stats::{reset_stats, stats, HookStats};
+#[cfg(feature = "tokio")]
+pub use stream::event_stream;
+pub use suppress::suppress_next;
+pub use watchdog::verify_hooks;
+pub use wheel::WheelDirection;
+pub use window::{ProcessMatch, WindowInfo};
+pub use worker::dropped_callback_events;
+
 use num_enum::TryFromPrimitive;
 use once_cell::sync::Lazy;
-use std::sync::RwLock;
-use windows::Win32::{
-    Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
-    UI::WindowsAndMessaging::{
-        CallNextHookEx, DispatchMessageW, GetMessageW, SetWindowsHookExW, TranslateMessage,
-        UnhookWindowsHookEx, HHOOK, HOOKPROC, KBDLLHOOKSTRUCT, MSG, MSLLHOOKSTRUCT, WH_KEYBOARD_LL,
-        WH_MOUSE_LL,
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError},
+    Arc,
+};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+use sync::{Mutex, RwLock};
+use windows::{
+    core::PCWSTR,
+    Win32::{
+        Foundation::{HANDLE, HINSTANCE, HWND, LPARAM, LRESULT, WAIT_OBJECT_0, WPARAM},
+        System::Threading::{CreateEventW, GetCurrentThreadId, ResetEvent, SetEvent},
+        UI::WindowsAndMessaging::{
+            CallNextHookEx, DispatchMessageW, KillTimer, MsgWaitForMultipleObjectsEx, PeekMessageW,
+            PostThreadMessageW, SetTimer, SetWindowsHookExW, TranslateMessage, UnhookWindowsHookEx,
+            HHOOK, HOOKPROC, KBDLLHOOKSTRUCT, LLKHF_INJECTED, LLKHF_LOWER_IL_INJECTED,
+            LLMHF_INJECTED, LLMHF_LOWER_IL_INJECTED, MSG, MSLLHOOKSTRUCT, MWMO_INPUTAVAILABLE,
+            PM_NOREMOVE, PM_REMOVE, QS_ALLINPUT, WHEEL_DELTA, WH_KEYBOARD, WH_KEYBOARD_LL,
+            WH_MOUSE, WH_MOUSE_LL, WM_LBUTTONDBLCLK, WM_LBUTTONDOWN, WM_LBUTTONUP,
+            WM_MBUTTONDBLCLK, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEHWHEEL, WM_MOUSEMOVE,
+            WM_MOUSEWHEEL, WM_NULL, WM_QUIT, WM_RBUTTONDBLCLK, WM_RBUTTONDOWN, WM_RBUTTONUP,
+            WM_TIMER, WM_XBUTTONDOWN, WM_XBUTTONUP, XBUTTON1, XBUTTON2,
+        },
     },
 };
 
-#[derive(Debug)]
+/// 事件的来源，由LLKHF_INJECTED/LLKHF_LOWER_IL_INJECTED等标志得到
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventSource {
+    /// 来自键盘、鼠标等硬件
+    Physical,
+    /// 由SendInput等方式注入
+    Injected,
+    /// 由完整性级别较低的进程注入，注重安全的程序可以忽略这类输入
+    InjectedLowerIL,
+}
+
+impl EventSource {
+    fn from_flags(injected: bool, lower_il_injected: bool) -> EventSource {
+        match (injected, lower_il_injected) {
+            (_, true) => EventSource::InjectedLowerIL,
+            (true, false) => EventSource::Injected,
+            (false, false) => EventSource::Physical,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyInfo {
+    pub vk_code: u32,
+    /// 硬件扫描码，可区分共用同一虚拟键码的物理按键
+    pub scan_code: u32,
+    /// 事件发生时间（开机以来的毫秒数，与GetTickCount一致）
+    pub time: u32,
+    /// 是否为SendInput等方式注入的事件
+    pub injected: bool,
+    /// 是否由较低完整性级别的进程注入
+    pub lower_il_injected: bool,
+    /// 处理完本次按键后的修饰键状态
+    pub modifiers: Modifiers,
+    /// 按住按键时系统自动重复产生的按下事件
+    pub is_repeat: bool,
+    /// 抬起事件中按键按住的时间，没有收到对应的按下事件时为None
+    pub held_for: Option<Duration>,
+    /// 是否为小键盘上的按键，用于区分小键盘数字与主键盘数字、小键盘方向键与独立方向键
+    pub is_numpad: bool,
+    /// 注入时通过dwExtraInfo附带的值，本库注入的事件为`INJECTED_EXTRA_INFO`
+    pub extra_info: usize,
+}
+
+impl KeyInfo {
+    /// 事件的来源，见`EventSource`
+    pub fn source(&self) -> EventSource {
+        EventSource::from_flags(self.injected, self.lower_il_injected)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KeyEvent {
-    KeyPress(u32),
-    KeyUp(u32),
+    KeyPress(KeyInfo),
+    KeyUp(KeyInfo),
 }
 
-#[derive(Default, PartialEq, Debug)]
+#[derive(Default, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: i32,
     pub y: i32,
 }
 
-#[derive(Debug, Eq, PartialEq, TryFromPrimitive)]
+impl Point {
+    /// 以虚拟桌面左上角为原点的坐标，多显示器时不会出现负数
+    pub fn in_virtual_desktop(&self) -> Point {
+        let (left, top, _, _) = input::virtual_desktop_bounds();
+        Point {
+            x: self.x - left,
+            y: self.y - top,
+        }
+    }
+
+    /// 在虚拟桌面中的相对位置，左上角为(0.0, 0.0)，右下角为(1.0, 1.0)
+    pub fn normalized(&self) -> (f32, f32) {
+        let (left, top, width, height) = input::virtual_desktop_bounds();
+        let scale = |v: i32, origin: i32, size: i32| {
+            if size <= 1 {
+                return 0.0;
+            }
+            (v - origin) as f32 / (size - 1) as f32
+        };
+        (scale(self.x, left, width), scale(self.y, top, height))
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseInfo {
+    pub point: Point,
+    /// 事件发生时间（开机以来的毫秒数，与GetTickCount一致）
+    pub time: u32,
+    /// 是否为SendInput等方式注入的事件
+    pub injected: bool,
+    /// 是否由较低完整性级别的进程注入
+    pub lower_il_injected: bool,
+    /// 滚轮滚动量，为WHEEL_DELTA(120)的倍数，非滚轮事件为0
+    ///
+    /// 垂直滚轮正数表示向前(远离用户)滚动；水平滚轮(`MouseHWheel`)正数表示向右滚动
+    pub wheel_delta: i16,
+    /// 滚轮事件的滚动方向，由`wheel_delta`的符号得到，非滚轮事件为None
+    pub wheel_direction: Option<WheelDirection>,
+    /// 按下位置的窗口，只在开启`HookConfig::window_info`时为鼠标按下事件填充
+    pub window: Option<WindowInfo>,
+    /// 相对前台窗口客户区的坐标，只在开启`HookConfig::client_pos`时为鼠标按键事件填充
+    pub client_pos: Option<Point>,
+    /// 鼠标按键抬起事件中按键按住的时间，没有收到对应的按下事件时为None
+    pub held_for: Option<Duration>,
+    /// 拖动事件的按键和起点，只在`DragStart`/`DragMove`/`DragEnd`中填充
+    pub drag: Option<DragInfo>,
+    /// 相对上一次派发的`MouseMove`的移动量，需要开启`HookConfig::mouse_move_deltas`，第一次移动为0
+    ///
+    /// 由光标位置计算，光标停在屏幕边缘时继续移动鼠标得到的是0，需要原始移动量时应使用Raw Input。
+    pub dx: i32,
+    /// 垂直方向的移动量，见`dx`
+    pub dy: i32,
+    /// 注入时通过dwExtraInfo附带的值，本库注入的事件为`INJECTED_EXTRA_INFO`
+    pub extra_info: usize,
+}
+
+impl MouseInfo {
+    /// 滚轮滚动的格数
+    pub fn notches(&self) -> f32 {
+        self.wheel_delta as f32 / WHEEL_DELTA as f32
+    }
+
+    /// 事件的来源，见`EventSource`
+    pub fn source(&self) -> EventSource {
+        EventSource::from_flags(self.injected, self.lower_il_injected)
+    }
+}
+
+/// 鼠标事件，默认为物理按键，`HookConfig::swap_buttons`可以改为按系统设置上报逻辑按键
+///
+/// 值与WM_*鼠标消息一致，可以通过`as i32`和`TryFrom<i32>`与消息互相转换，因此不提供带字段的
+/// `Button { button, action, pos }`变体；按键与动作分开匹配使用`MouseEvent::button`或
+/// `Event::mouse_button`。
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum MouseEvent {
-    MouseMove = 0x200,
-    MouseLeftBUttonDown = 0x201,
-    MouseLeftButtonUp = 0x202,
-    MouseRightButtonDown = 0x204,
-    MouseRightButtonUp = 0x205,
-    MouseWheelRouting = 0x20A,
-    MouseMiddleButtonDown = 0x2b,
-    MouseMiddleButtonUp = 0x20c,
+    MouseMove = WM_MOUSEMOVE as i32,
+    #[cfg_attr(feature = "serde", serde(alias = "MouseLeftBUttonDown"))]
+    MouseLeftButtonDown = WM_LBUTTONDOWN as i32,
+    MouseLeftButtonUp = WM_LBUTTONUP as i32,
+    MouseRightButtonDown = WM_RBUTTONDOWN as i32,
+    MouseRightButtonUp = WM_RBUTTONUP as i32,
+    //双击事件由钩子根据两次按下合成，低级钩子本身不会收到WM_LBUTTONDBLCLK等消息
+    MouseLeftDoubleClick = WM_LBUTTONDBLCLK as i32,
+    MouseRightDoubleClick = WM_RBUTTONDBLCLK as i32,
+    MouseMiddleDoubleClick = WM_MBUTTONDBLCLK as i32,
+    #[cfg_attr(feature = "serde", serde(alias = "MouseWheelRouting"))]
+    MouseWheel = WM_MOUSEWHEEL as i32,
+    MouseMiddleButtonDown = WM_MBUTTONDOWN as i32,
+    MouseMiddleButtonUp = WM_MBUTTONUP as i32,
+    MouseHWheel = WM_MOUSEHWHEEL as i32,
+    //侧键共用WM_XBUTTONDOWN/WM_XBUTTONUP，高16位为mouseData中的XBUTTON1/XBUTTON2
+    MouseXButton1Down = (XBUTTON1.0 << 16 | WM_XBUTTONDOWN) as i32,
+    MouseXButton1Up = (XBUTTON1.0 << 16 | WM_XBUTTONUP) as i32,
+    MouseXButton2Down = (XBUTTON2.0 << 16 | WM_XBUTTONDOWN) as i32,
+    MouseXButton2Up = (XBUTTON2.0 << 16 | WM_XBUTTONUP) as i32,
+    //拖动事件由钩子根据按键和移动事件合成，`MouseInfo::drag`中为按键和起点
+    DragStart = 0x1000_0001,
+    DragMove = 0x1000_0002,
+    DragEnd = 0x1000_0003,
+    //整格滚轮事件由钩子累计滚轮滚动量合成，`MouseInfo::wheel_delta`为WHEEL_DELTA的整数倍
+    MouseWheelNotch = 0x1000_0004,
+    MouseHWheelNotch = 0x1000_0005,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ButtonAction {
+    Down,
+    Up,
 }
 
-#[derive(Debug)]
+//旧名称，保留一个版本
+#[allow(non_upper_case_globals)]
+impl MouseEvent {
+    #[deprecated(note = "拼写错误，请使用`MouseEvent::MouseLeftButtonDown`")]
+    pub const MouseLeftBUttonDown: MouseEvent = MouseEvent::MouseLeftButtonDown;
+    #[deprecated(note = "请使用`MouseEvent::MouseWheel`")]
+    pub const MouseWheelRouting: MouseEvent = MouseEvent::MouseWheel;
+}
+
+impl MouseEvent {
+    /// 按键事件对应的鼠标按键和动作，移动、滚轮、双击事件返回None
+    pub fn button(&self) -> Option<(MouseButton, ButtonAction)> {
+        use ButtonAction::*;
+        use MouseButton::*;
+        Some(match self {
+            MouseEvent::MouseLeftButtonDown => (Left, Down),
+            MouseEvent::MouseLeftButtonUp => (Left, Up),
+            MouseEvent::MouseRightButtonDown => (Right, Down),
+            MouseEvent::MouseRightButtonUp => (Right, Up),
+            MouseEvent::MouseMiddleButtonDown => (Middle, Down),
+            MouseEvent::MouseMiddleButtonUp => (Middle, Up),
+            MouseEvent::MouseXButton1Down => (X1, Down),
+            MouseEvent::MouseXButton1Up => (X1, Up),
+            MouseEvent::MouseXButton2Down => (X2, Down),
+            MouseEvent::MouseXButton2Up => (X2, Up),
+            _ => return None,
+        })
+    }
+
+    /// 由鼠标按键和动作得到对应的事件
+    pub fn from_button(button: MouseButton, action: ButtonAction) -> MouseEvent {
+        match (button, action) {
+            (MouseButton::Left, ButtonAction::Down) => MouseEvent::MouseLeftButtonDown,
+            (MouseButton::Left, ButtonAction::Up) => MouseEvent::MouseLeftButtonUp,
+            (MouseButton::Right, ButtonAction::Down) => MouseEvent::MouseRightButtonDown,
+            (MouseButton::Right, ButtonAction::Up) => MouseEvent::MouseRightButtonUp,
+            (MouseButton::Middle, ButtonAction::Down) => MouseEvent::MouseMiddleButtonDown,
+            (MouseButton::Middle, ButtonAction::Up) => MouseEvent::MouseMiddleButtonUp,
+            (MouseButton::X1, ButtonAction::Down) => MouseEvent::MouseXButton1Down,
+            (MouseButton::X1, ButtonAction::Up) => MouseEvent::MouseXButton1Up,
+            (MouseButton::X2, ButtonAction::Down) => MouseEvent::MouseXButton2Down,
+            (MouseButton::X2, ButtonAction::Up) => MouseEvent::MouseXButton2Up,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Event {
     KeyEvent(KeyEvent),
-    MouseEvent((MouseEvent, Point)),
+    MouseEvent((MouseEvent, MouseInfo)),
+    /// 钩子已安装，消息循环开始运行(需要开启`HookConfig::lifecycle_events`)
+    HookStarted,
+    /// 钩子已卸载，消息循环即将返回(需要开启`HookConfig::lifecycle_events`)
+    HookStopped,
+    /// 一次事件的回调和监听器执行时间超过`HookConfig::slow_callback_threshold`，在该事件处理完成后派发
+    CallbackSlow {
+        duration: Duration,
+    },
+    /// 钩子被系统卸载后已重新安装(需要开启`HookConfig::reinstall_hooks`)，失效期间的输入已经丢失
+    HookReinstalled,
+    /// 派发速率超过`HookConfig::overload_limit`或回调队列积压，上一秒内丢弃了`dropped`个移动、滚轮事件
+    Overload {
+        dropped: u64,
+    },
+}
+
+impl Event {
+    /// 事件发生时间（开机以来的毫秒数），生命周期事件和`CallbackSlow`/`Overload`没有时间戳，返回0
+    pub fn time(&self) -> u32 {
+        match self {
+            Event::KeyEvent(KeyEvent::KeyPress(info) | KeyEvent::KeyUp(info)) => info.time,
+            Event::MouseEvent((_, info)) => info.time,
+            Event::HookStarted
+            | Event::HookStopped
+            | Event::CallbackSlow { .. }
+            | Event::HookReinstalled
+            | Event::Overload { .. } => 0,
+        }
+    }
+
+    /// 键盘、鼠标事件的来源，生命周期和诊断事件返回None
+    pub fn source(&self) -> Option<EventSource> {
+        match self {
+            Event::KeyEvent(KeyEvent::KeyPress(info) | KeyEvent::KeyUp(info)) => {
+                Some(info.source())
+            }
+            Event::MouseEvent((_, info)) => Some(info.source()),
+            _ => None,
+        }
+    }
+
+    /// 鼠标按键事件的按键、动作和位置，便于用一个分支匹配"任意按键按下"
+    pub fn mouse_button(&self) -> Option<(MouseButton, ButtonAction, &Point)> {
+        match self {
+            Event::MouseEvent((event, info)) => {
+                let (button, action) = event.button()?;
+                Some((button, action, &info.point))
+            }
+            _ => None,
+        }
+    }
+
+    /// 键盘事件的按键信息
+    pub fn key_info(&self) -> Option<&KeyInfo> {
+        match self {
+            Event::KeyEvent(KeyEvent::KeyPress(info) | KeyEvent::KeyUp(info)) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// 键盘事件的虚拟键码
+    pub fn key_code(&self) -> Option<u32> {
+        self.key_info().map(|info| info.vk_code)
+    }
+
+    /// 键盘事件是否为按下和虚拟键码
+    pub fn key(&self) -> Option<(bool, u32)> {
+        match self {
+            Event::KeyEvent(KeyEvent::KeyPress(info)) => Some((true, info.vk_code)),
+            Event::KeyEvent(KeyEvent::KeyUp(info)) => Some((false, info.vk_code)),
+            _ => None,
+        }
+    }
+
+    pub fn is_key_press(&self) -> bool {
+        matches!(self, Event::KeyEvent(KeyEvent::KeyPress(_)))
+    }
+
+    pub fn is_key_up(&self) -> bool {
+        matches!(self, Event::KeyEvent(KeyEvent::KeyUp(_)))
+    }
+
+    /// 按键按下事件的虚拟键码，抬起事件返回None
+    pub fn as_key_press(&self) -> Option<u32> {
+        match self {
+            Event::KeyEvent(KeyEvent::KeyPress(info)) => Some(info.vk_code),
+            _ => None,
+        }
+    }
+
+    /// 鼠标事件的类型和信息
+    pub fn mouse_info(&self) -> Option<(MouseEvent, &MouseInfo)> {
+        match self {
+            Event::MouseEvent((event, info)) => Some((*event, info)),
+            _ => None,
+        }
+    }
+
+    /// 鼠标事件的位置
+    pub fn mouse_position(&self) -> Option<&Point> {
+        self.mouse_info().map(|(_, info)| &info.point)
+    }
+
+    /// 同`mouse_position`
+    pub fn position(&self) -> Option<&Point> {
+        self.mouse_position()
+    }
+
+    /// 是否为`button`的按下或抬起事件
+    pub fn is_mouse_button(&self, button: MouseButton) -> bool {
+        matches!(self.mouse_button(), Some((b, _, _)) if b == button)
+    }
+
+    /// 是否为`MouseMove`，合成的拖动事件返回false
+    pub fn is_mouse_move(&self) -> bool {
+        matches!(self, Event::MouseEvent((MouseEvent::MouseMove, _)))
+    }
+
+    /// 鼠标按键抬起(完成一次点击)时的按键和位置
+    pub fn as_click(&self) -> Option<(MouseButton, &Point)> {
+        match self.mouse_button()? {
+            (button, ButtonAction::Up, point) => Some((button, point)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for KeyEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, info) = match self {
+            KeyEvent::KeyPress(info) => ("KeyPress", info),
+            KeyEvent::KeyUp(info) => ("KeyUp", info),
+        };
+        match key_name(info.vk_code) {
+            Some(name) => write!(f, "{}({})", kind, name),
+            None => write!(f, "{}(0x{:02X})", kind, info.vk_code),
+        }
+    }
+}
+
+impl fmt::Display for MouseEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::KeyEvent(event) => event.fmt(f),
+            Event::MouseEvent((
+                event @ (MouseEvent::MouseWheel
+                | MouseEvent::MouseHWheel
+                | MouseEvent::MouseWheelNotch
+                | MouseEvent::MouseHWheelNotch),
+                info,
+            )) => {
+                write!(
+                    f,
+                    "{}({}, {}, {})",
+                    event, info.point.x, info.point.y, info.wheel_delta
+                )
+            }
+            Event::MouseEvent((event, info)) => {
+                write!(f, "{}({}, {})", event, info.point.x, info.point.y)
+            }
+            Event::HookStarted => write!(f, "HookStarted"),
+            Event::HookStopped => write!(f, "HookStopped"),
+            Event::CallbackSlow { duration } => write!(f, "CallbackSlow({:?})", duration),
+            Event::HookReinstalled => write!(f, "HookReinstalled"),
+            Event::Overload { dropped } => write!(f, "Overload({})", dropped),
+        }
+    }
+}
+
+/// 回调的返回值，决定事件是否继续传递给下一个钩子/目标程序
+///
+/// `Block` 只对系统允许拦截的事件生效：键盘按键与鼠标按键、滚轮可以被吞掉。
+/// 拦截 `MouseMove` 会让光标停止移动，风险很高，默认忽略，需要时启用`HookConfig::block_mouse_move`。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatch {
+    /// 继续传递（默认行为）
+    #[default]
+    Pass,
+    /// 拦截事件，不再调用 CallNextHookEx
+    Block,
 }
 
 type HookFn = unsafe extern "system" fn(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT;
-type EventCallback = fn(Event);
+type EventCallback = fn(Event) -> Dispatch;
+type EventFilter = fn(&Event) -> bool;
+type BoxedCallback = Box<dyn FnMut(Event) -> Dispatch + Send>;
+pub type HookHandle = std::thread::JoinHandle<Result<()>>;
+//(钩子类型, 是否安装, 结果)
+type ToggleRequest = (HookKind, bool, SyncSender<Result<()>>);
 
 static MOUSE_HOOK: Lazy<RwLock<Option<HHOOK>>> = Lazy::new(|| RwLock::new(None));
 static KEYBOARD_HOOK: Lazy<RwLock<Option<HHOOK>>> = Lazy::new(|| RwLock::new(None));
-static CALLBACK: Lazy<RwLock<Option<EventCallback>>> = Lazy::new(|| RwLock::new(None));
-static EXIT: Lazy<RwLock<bool>> = Lazy::new(|| RwLock::new(false));
+static FILTER: Lazy<RwLock<Option<EventFilter>>> = Lazy::new(|| RwLock::new(None));
+static CALLBACK: Lazy<Mutex<Option<BoxedCallback>>> = Lazy::new(|| Mutex::new(None));
+static EXIT: AtomicBool = AtomicBool::new(false);
+static RUNNING: AtomicBool = AtomicBool::new(false);
+//`start_hook_async`启动、正在运行的钩子线程，用于判断`HookHandle`是否拥有正在运行的钩子
+static HOOK_OWNER: Lazy<Mutex<Option<ThreadId>>> = Lazy::new(|| Mutex::new(None));
+static PAUSED: AtomicBool = AtomicBool::new(false);
+//钩子由install_hooks安装，使用调用者的消息循环
+static EXTERNAL_LOOP: AtomicBool = AtomicBool::new(false);
+static HOOK_THREAD_ID: Lazy<RwLock<Option<u32>>> = Lazy::new(|| RwLock::new(None));
+static EVENT_SENDER: Lazy<Mutex<Option<SyncSender<Event>>>> = Lazy::new(|| Mutex::new(None));
+static CONFIG: Lazy<RwLock<HookConfig>> = Lazy::new(|| RwLock::new(HookConfig::default()));
+//按下的按键及其按下时间
+static KEYS_DOWN: Lazy<RwLock<HashMap<u32, u32>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+//按下的鼠标按键及其按下时间
+static BUTTONS_DOWN: Lazy<Mutex<HashMap<MouseButton, u32>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+//等待下一个事件的next_event调用及其编号，超时返回时按编号移除
+static NEXT_EVENT_WAITERS: Lazy<Mutex<HashMap<u64, SyncSender<Event>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_WAITER_ID: AtomicU64 = AtomicU64::new(0);
+//上一次派发的MouseMove的时间和位置
+static LAST_MOVE_TIME: Lazy<Mutex<Option<u32>>> = Lazy::new(|| Mutex::new(None));
+static LAST_MOVE_POINT: Lazy<Mutex<Option<Point>>> = Lazy::new(|| Mutex::new(None));
+//等待在钩子线程上执行的钩子开关请求
+static TOGGLE_REQUESTS: Lazy<Mutex<Vec<ToggleRequest>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static MODIFIERS: Lazy<RwLock<Modifiers>> = Lazy::new(|| RwLock::new(Modifiers::empty()));
+//紧急停止使用的修饰键状态，在防抖、suppress_next和重映射之前更新，被拦截或重映射的修饰键同样计入
+static KILL_MODIFIERS: Lazy<RwLock<Modifiers>> = Lazy::new(|| RwLock::new(Modifiers::empty()));
+//手动重置事件，安装钩子时重置，stop_hook和卸载钩子时设置，唤醒等待中的消息循环；
+//创建失败时消息循环只依靠超时检查EXIT
+static STOP_EVENT: Lazy<Option<HANDLE>> =
+    Lazy::new(|| unsafe { CreateEventW(None, true, false, PCWSTR::null()).ok() });
+//消息循环等待消息的最长时间(毫秒)，超时后重新检查退出标志
+const LOOP_WAIT_MS: u32 = 100;
 
 pub fn set_hook_callback(callback: EventCallback) -> Result<()> {
-    CALLBACK
-        .write()
-        .map_err(|err| anyhow!("{:?}", err))?
-        .replace(callback);
+    set_hook_callback_boxed(Box::new(callback))
+}
+
+/// 设置闭包回调，可以捕获channel、计数器等状态
+///
+/// 回调默认在钩子线程中同步执行，耗时的回调请启用`HookConfig::callback_worker`，
+/// 否则钩子函数超时后可能被系统卸载。
+pub fn set_hook_callback_boxed(callback: BoxedCallback) -> Result<()> {
+    CALLBACK.lock().replace(callback);
+    Ok(())
+}
+
+/// 设置带状态的回调，回调通过`&T`访问状态，需要修改状态时可以在`T`中使用`Mutex`
+pub fn set_hook_callback_with<T: Send + Sync + 'static>(
+    state: Arc<T>,
+    callback: fn(&T, Event) -> Dispatch,
+) -> Result<()> {
+    set_hook_callback_boxed(Box::new(move |event| callback(&state, event)))
+}
+
+/// 设置事件过滤器，返回false的事件不会派发给回调、监听器、通道和录制，但仍然传递给下一个钩子
+pub fn set_event_filter(filter: EventFilter) -> Result<()> {
+    FILTER.write().replace(filter);
+    Ok(())
+}
+
+/// 移除事件过滤器
+pub fn clear_event_filter() -> Result<()> {
+    FILTER.write().take();
+    Ok(())
+}
+
+/// 在新线程中启动钩子，钩子已经在运行时线程返回`KbdmsError::AlreadyRunning`
+pub fn start_hook_async(hook_mouse: bool, hook_keyboard: bool) -> HookHandle {
+    start_hook_async_with(HookConfig::new().mouse(hook_mouse).keyboard(hook_keyboard))
+}
+
+/// 按配置在新线程中启动钩子
+pub fn start_hook_async_with(config: HookConfig) -> HookHandle {
+    spawn_hook(config, None)
+}
+
+/// 在新线程中启动钩子，`installed`收到安装钩子的结果；没有拿到运行标志时线程直接退出，不会发送
+pub(crate) fn spawn_hook(
+    config: HookConfig,
+    installed: Option<SyncSender<Result<()>>>,
+) -> HookHandle {
+    //记录下线程之前持有锁，钩子线程退出时等待记录完成再清除
+    let mut owner = HOOK_OWNER.lock();
+    //在启动线程前占用运行标志并清除退出标志，避免线程启动前调用的stop_hook被覆盖
+    let running = RunningGuard::acquire();
+    let owns = running.is_ok();
+    if owns {
+        EXIT.store(false, Ordering::Release);
+    }
+    //标准库会同时通过SetThreadDescription设置线程描述，调试器和Process Explorer中可以看到
+    let handle = std::thread::Builder::new()
+        .name("kbdmshook-loop".to_string())
+        .spawn(move || {
+            let _running = running?;
+            let _owner = OwnerGuard;
+            run_hook(config, installed)
+        })
+        .expect("failed to spawn hook thread");
+    if owns {
+        owner.replace(handle.thread().id());
+    }
+    handle
+}
+
+/// 只有`handle`的线程拥有正在运行的钩子时才调用`stop_hook`
+///
+/// 因为`AlreadyRunning`启动失败的句柄不拥有钩子，不能停掉其他正在运行的钩子。
+pub(crate) fn stop_owned_hook(handle: &HookHandle) -> Result<()> {
+    //持有锁期间钩子线程无法清除记录，不会误停之后启动的钩子
+    let owner = HOOK_OWNER.lock();
+    if *owner == Some(handle.thread().id()) {
+        stop_hook()?;
+    }
     Ok(())
 }
 
-pub fn start_hook_async(
+/// 事件通道的容量
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 启动钩子，并通过通道接收事件
+///
+/// 通道容量为 [`EVENT_CHANNEL_CAPACITY`]，接收端处理不及时导致通道已满时丢弃最新的事件，
+/// 钩子线程不会因此阻塞。通道与回调可以同时使用。
+pub fn start_hook_channel(
     hook_mouse: bool,
     hook_keyboard: bool,
-) -> std::thread::JoinHandle<std::result::Result<(), anyhow::Error>> {
-    std::thread::spawn(move || start_hook(hook_mouse, hook_keyboard))
+) -> Result<(HookHandle, Receiver<Event>)> {
+    let (sender, receiver) = sync_channel(EVENT_CHANNEL_CAPACITY);
+    EVENT_SENDER.lock().replace(sender);
+    Ok((start_hook_async(hook_mouse, hook_keyboard), receiver))
+}
+
+/// 在当前线程安装钩子后立即返回，由调用者已有的消息循环(winit、egui等)分发钩子消息
+///
+/// 低级钩子的回调在安装钩子的线程上执行，该线程必须持续处理消息，否则系统会在超时后跳过钩子。
+/// 安装后`is_running`返回true，需要调用`uninstall_hooks`卸载；`stop_hook`只会卸载钩子，
+/// 不会向调用者的线程投递WM_QUIT。
+pub fn install_hooks(config: HookConfig) -> Result<()> {
+    let running = RunningGuard::acquire()?;
+    install(config)?;
+    std::mem::forget(running);
+    EXTERNAL_LOOP.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// 卸载`install_hooks`安装的钩子
+pub fn uninstall_hooks() -> Result<()> {
+    if !EXTERNAL_LOOP.swap(false, Ordering::AcqRel) {
+        return Err(KbdmsError::NotRunning);
+    }
+    let result = uninstall();
+    RUNNING.store(false, Ordering::Release);
+    result
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HookKind {
+    Keyboard,
+    Mouse,
+}
+
+/// 在正在运行的消息循环中安装键盘钩子，例如只启动了鼠标钩子、之后才需要键盘事件
+///
+/// 低级钩子必须在运行消息循环的线程上安装，请求会投递到钩子线程执行并等待结果。
+/// 使用`install_hooks`时没有钩子线程，需要在调用`install_hooks`的线程上调用。
+/// 键盘钩子已经安装时返回`KbdmsError::AlreadyHooked`。
+pub fn enable_keyboard_hook() -> Result<()> {
+    toggle_hook(HookKind::Keyboard, true)
+}
+
+/// 卸载键盘钩子，消息循环和鼠标钩子继续运行，之后可以用`enable_keyboard_hook`重新安装
+///
+/// 卸载期间收不到按键事件，按下的按键和修饰键状态会被清空。
+pub fn disable_keyboard_hook() -> Result<()> {
+    toggle_hook(HookKind::Keyboard, false)
+}
+
+/// 在正在运行的消息循环中安装鼠标钩子，见`enable_keyboard_hook`
+pub fn enable_mouse_hook() -> Result<()> {
+    toggle_hook(HookKind::Mouse, true)
+}
+
+/// 卸载鼠标钩子，消息循环和键盘钩子继续运行
+pub fn disable_mouse_hook() -> Result<()> {
+    toggle_hook(HookKind::Mouse, false)
+}
+
+fn toggle_hook(kind: HookKind, enable: bool) -> Result<()> {
+    if !is_running() {
+        return Err(KbdmsError::NotRunning);
+    }
+    let thread_id = match hook_thread_id() {
+        //在钩子线程上(例如回调中)调用时直接执行，投递后等待会死锁
+        Some(thread_id) if thread_id == unsafe { GetCurrentThreadId() } => {
+            return apply_toggle(kind, enable)
+        }
+        Some(thread_id) => thread_id,
+        None if EXTERNAL_LOOP.load(Ordering::Acquire) => return apply_toggle(kind, enable),
+        None => return Err(KbdmsError::NotRunning),
+    };
+    let (sender, receiver) = sync_channel(1);
+    TOGGLE_REQUESTS.lock().push((kind, enable, sender));
+    //消息本身不做任何事，只是唤醒消息循环
+    unsafe {
+        let _ = PostThreadMessageW(thread_id, WM_NULL, WPARAM(0), LPARAM(0));
+    }
+    loop {
+        if !is_running() {
+            return Err(KbdmsError::NotRunning);
+        }
+        match receiver.recv_timeout(Duration::from_millis(50)) {
+            Ok(result) => return result,
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return Err(KbdmsError::NotRunning),
+        }
+    }
+}
+
+/// 在钩子线程上执行等待中的钩子开关请求
+fn process_toggle_requests() {
+    let requests = std::mem::take(&mut *TOGGLE_REQUESTS.lock());
+    for (kind, enable, sender) in requests {
+        let _ = sender.try_send(apply_toggle(kind, enable));
+    }
+}
+
+fn apply_toggle(kind: HookKind, enable: bool) -> Result<()> {
+    match (kind, enable) {
+        (HookKind::Keyboard, true) => set_keyboard_hook(),
+        (HookKind::Keyboard, false) => {
+            remove_keyboard_hook()?;
+            //卸载期间的按键抬起收不到，清空状态避免按键一直处于按下状态
+            remap::release_all();
+            KEYS_DOWN.write().clear();
+            *MODIFIERS.write() = Modifiers::empty();
+            *KILL_MODIFIERS.write() = Modifiers::empty();
+            double_tap::reset();
+            Ok(())
+        }
+        (HookKind::Mouse, true) => set_mouse_hook(),
+        (HookKind::Mouse, false) => {
+            remove_mouse_hook()?;
+            BUTTONS_DOWN.lock().clear();
+            double_click::reset();
+            drag::reset();
+            Ok(())
+        }
+    }
+}
+
+/// 阻塞等待钩子收到的下一个事件，超时或钩子停止时返回None
+///
+/// 钩子需要已经通过`start_hook_async`等方式在其他线程中运行，钩子没有运行时立即返回None。
+pub fn next_event(timeout: Option<Duration>) -> Option<Event> {
+    if !is_running() {
+        return None;
+    }
+    let (sender, receiver) = sync_channel(1);
+    let id = NEXT_WAITER_ID.fetch_add(1, Ordering::Relaxed);
+    NEXT_EVENT_WAITERS.lock().insert(id, sender);
+    //没有收到事件就返回时移除自己，反复超时轮询时列表不会增长
+    let give_up = || {
+        NEXT_EVENT_WAITERS.lock().remove(&id);
+        //移除之前可能刚好收到了事件
+        receiver.try_recv().ok()
+    };
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    //分段等待，钩子停止后及时返回
+    loop {
+        if !is_running() {
+            return give_up();
+        }
+        let mut wait = Duration::from_millis(50);
+        if let Some(deadline) = deadline {
+            let now = Instant::now();
+            if now >= deadline {
+                return give_up();
+            }
+            wait = wait.min(deadline - now);
+        }
+        match receiver.recv_timeout(wait) {
+            Ok(event) => return Some(event),
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return None,
+        }
+    }
 }
 
 pub fn stop_hook() -> Result<()> {
-    *EXIT.write().map_err(|err| anyhow!("{:?}", err))? = true;
+    EXIT.store(true, Ordering::Release);
     let _ = remove_keyboard_hook();
     let _ = remove_mouse_hook();
+    //唤醒等待中的消息循环，WM_QUIT同样会结束循环
+    signal_stop_event();
+    if let Some(thread_id) = *HOOK_THREAD_ID.read() {
+        unsafe {
+            let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+        }
+    }
     Ok(())
 }
 
+fn signal_stop_event() {
+    if let Some(event) = *STOP_EVENT {
+        unsafe {
+            SetEvent(event);
+        }
+    }
+}
+
+/// 钩子停止时设置的事件对象，可以与其他句柄一起传给WaitForMultipleObjects等待钩子停止
+///
+/// 手动重置事件，在`stop_hook`调用时和钩子卸载后设置，每次安装钩子时重置。
+/// 句柄由本库持有，不要关闭或重置它。创建事件失败时返回None。
+pub fn stop_event_handle() -> Option<HANDLE> {
+    *STOP_EVENT
+}
+
+/// 钩子消息循环所在线程的id，可以用PostThreadMessageW投递WM_APP等自定义消息，钩子没有运行时返回None
+///
+/// 消息循环会对收到的线程消息调用TranslateMessage/DispatchMessageW，不会转交给回调。
+pub fn hook_thread_id() -> Option<u32> {
+    *HOOK_THREAD_ID.read()
+}
+
+/// 暂停派发事件，钩子和消息循环保持运行，暂停期间的事件直接传递给下一个钩子
+///
+/// 暂停期间防抖、`suppress_next`、屏蔽和重映射都不生效，只有紧急停止组合键仍然有效；
+/// 暂停前按下、被热键拦截或重映射的按键，抬起时仍然拦截或释放目标按键。
+pub fn pause() {
+    PAUSED.store(true, Ordering::Release);
+}
+
+/// 恢复派发事件
+pub fn resume() {
+    PAUSED.store(false, Ordering::Release);
+}
+
+/// 是否已通过`pause`暂停派发事件
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Acquire)
+}
+
+/// 钩子记录的当前修饰键状态
+pub fn current_modifiers() -> Modifiers {
+    *MODIFIERS.read()
+}
+
+/// 钩子认为当前按下的虚拟键码(左右修饰键为区分左右的键码)，按键码排序
+///
+/// 集合只根据钩子收到的事件维护：钩子安装前按下的键不会出现；其他桌面(如UAC提示、锁屏)
+/// 或更高权限窗口中抬起按键时钩子收不到抬起事件，按键会一直留在集合中，
+/// 可以调用`reconcile_keys_down`修正。
+pub fn keys_down() -> Vec<u32> {
+    let mut keys: Vec<u32> = KEYS_DOWN.read().keys().copied().collect();
+    keys.sort_unstable();
+    keys
+}
+
+/// 通过`is_key_down`移除实际已经抬起的按键，返回被移除的键码
+///
+/// 低级钩子回调执行时系统按键状态还没有更新，不要在回调中调用。
+pub fn reconcile_keys_down() -> Result<Vec<u32>> {
+    let mut removed = Vec::new();
+    KEYS_DOWN.write().retain(|&vk, _| {
+        let down = is_key_down(vk);
+        if !down {
+            removed.push(vk);
+        }
+        down
+    });
+    removed.sort_unstable();
+    Ok(removed)
+}
+
+/// 在当前线程安装钩子并运行消息循环，钩子已经在运行时返回`KbdmsError::AlreadyRunning`
 pub fn start_hook(hook_mouse: bool, hook_keyboard: bool) -> Result<()> {
+    start_hook_with(HookConfig::new().mouse(hook_mouse).keyboard(hook_keyboard))
+}
+
+/// 按配置在当前线程安装钩子并运行消息循环
+pub fn start_hook_with(config: HookConfig) -> Result<()> {
+    let _running = RunningGuard::acquire()?;
+    EXIT.store(false, Ordering::Release);
+    run_hook(config, None)
+}
+
+/// 钩子的消息循环是否正在运行
+pub fn is_running() -> bool {
+    RUNNING.load(Ordering::Acquire)
+}
+
+//消息循环退出(包括出错返回)时清除运行标志
+struct RunningGuard;
+
+impl RunningGuard {
+    fn acquire() -> Result<RunningGuard> {
+        RUNNING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map(|_| RunningGuard)
+            .map_err(|_| KbdmsError::AlreadyRunning)
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::Release);
+    }
+}
+
+//钩子线程退出时清除`HOOK_OWNER`中的记录，需要先于RunningGuard释放
+struct OwnerGuard;
+
+impl Drop for OwnerGuard {
+    fn drop(&mut self) {
+        let mut owner = HOOK_OWNER.lock();
+        if *owner == Some(std::thread::current().id()) {
+            owner.take();
+        }
+    }
+}
+
+/// 保存配置、重置状态并安装钩子
+fn install(config: HookConfig) -> Result<()> {
+    let (hook_mouse, hook_keyboard) = (config.mouse, config.keyboard);
+    let history_capacity = config.recent_events;
+    //重置事件之前调用的stop_hook已经设置了EXIT，消息循环开始前会检查
+    if let Some(event) = *STOP_EVENT {
+        unsafe {
+            ResetEvent(event);
+        }
+    }
+    match config.callback_worker {
+        Some(capacity) => worker::start(capacity)?,
+        None => worker::stop(),
+    }
+    *CONFIG.write() = config;
+    KEYS_DOWN.write().clear();
+    BUTTONS_DOWN.lock().clear();
+    debounce::reset();
+    double_click::reset();
+    double_tap::reset();
+    drag::reset();
+    wheel::reset();
+    history::set_capacity(history_capacity);
+    LAST_MOVE_TIME.lock().take();
+    LAST_MOVE_POINT.lock().take();
     if hook_keyboard {
-        set_keyboard_hook(keyboard_hook_proc)?;
+        set_keyboard_hook()?;
     }
     if hook_mouse {
-        set_mouse_hook(mouse_hook_proc)?;
+        if let Err(err) = set_mouse_hook() {
+            if hook_keyboard {
+                let _ = remove_keyboard_hook();
+            }
+            return Err(err);
+        }
     }
-    {
-        *EXIT.write().map_err(|err| anyhow!("{:?}", err))? = false;
+    watchdog::reset();
+    window::reset_foreground();
+    overload::reset();
+    if CONFIG.read().lifecycle_events {
+        dispatch_event(Event::HookStarted);
     }
+    Ok(())
+}
+
+/// 卸载钩子并唤醒等待事件的调用
+fn uninstall() -> Result<()> {
+    //唤醒正在等待的next_event
+    NEXT_EVENT_WAITERS.lock().clear();
+    TOGGLE_REQUESTS.lock().clear();
+    let _ = remove_keyboard_hook();
+    let _ = remove_mouse_hook();
+    remap::release_all();
+    if CONFIG.read().lifecycle_events {
+        dispatch_event(Event::HookStopped);
+    }
+    worker::stop();
+    signal_stop_event();
+    Ok(())
+}
+
+fn run_hook(config: HookConfig, installed: Option<SyncSender<Result<()>>>) -> Result<()> {
+    let result = install(config);
+    if let Some(installed) = installed {
+        let _ = installed.send(result.clone());
+    }
+    result?;
     unsafe {
         let mut msg = MSG::default();
-        while GetMessageW(&mut msg, HWND(0), 0, 0).0 > 0 {
-            let exit = *EXIT.read().map_err(|err| anyhow!("{:?}", err))?;
-            if exit {
+        //确保线程消息队列已创建，PostThreadMessageW才能投递成功
+        PeekMessageW(&mut msg, HWND(0), 0, 0, PM_NOREMOVE);
+        HOOK_THREAD_ID.write().replace(GetCurrentThreadId());
+        //线程计时器，定期检查钩子是否被系统卸载
+        let timer = match CONFIG.read().reinstall_hooks {
+            Some(interval) => SetTimer(HWND(0), 0, interval.as_millis() as u32, None),
+            None => 0,
+        };
+        let handles: Vec<HANDLE> = STOP_EVENT.iter().copied().collect();
+        //等待消息或停止事件，超时后也会重新检查EXIT，消息循环空闲时同样能及时退出
+        'run: while !EXIT.load(Ordering::Acquire) {
+            let wait = MsgWaitForMultipleObjectsEx(
+                Some(&handles),
+                LOOP_WAIT_MS,
+                QS_ALLINPUT,
+                MWMO_INPUTAVAILABLE,
+            );
+            if !handles.is_empty() && wait == WAIT_OBJECT_0.0 {
                 break;
             }
-            TranslateMessage(&msg);
-            DispatchMessageW(&msg);
+            process_toggle_requests();
+            //PeekMessageW同时会调用等待中的钩子函数
+            while PeekMessageW(&mut msg, HWND(0), 0, 0, PM_REMOVE).as_bool() {
+                if msg.message == WM_QUIT || EXIT.load(Ordering::Acquire) {
+                    break 'run;
+                }
+                if timer != 0 && msg.message == WM_TIMER && msg.wParam.0 == timer {
+                    if !verify_hooks() {
+                        reinstall_hooks();
+                    }
+                    continue;
+                }
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+        if timer != 0 {
+            KillTimer(HWND(0), timer);
         }
     }
-    Ok(())
+    HOOK_THREAD_ID.write().take();
+    uninstall()
 }
 
-unsafe extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
-    if let Ok(callback) = CALLBACK.read() {
-        if let Some(callback) = callback.as_ref() {
-            let data = lparam.0 as *const KBDLLHOOKSTRUCT;
-            if !data.is_null() {
-                let data: &KBDLLHOOKSTRUCT = &*data;
-                match wparam {
-                    WPARAM(0x100) => {
-                        //普通键按下
-                        callback(Event::KeyEvent(KeyEvent::KeyPress(data.vkCode)));
-                    }
-                    WPARAM(0x101) => {
-                        //普通键抬起
-                        callback(Event::KeyEvent(KeyEvent::KeyUp(data.vkCode)));
-                    }
-                    WPARAM(0x104) => {
-                        //系统键按下
-                        callback(Event::KeyEvent(KeyEvent::KeyPress(data.vkCode)));
-                    }
-                    WPARAM(0x105) => {
-                        //系统键抬起
-                        callback(Event::KeyEvent(KeyEvent::KeyUp(data.vkCode)));
-                    }
-                    _ => (),
-                };
+/// 重新安装已安装的钩子，旧的钩子句柄已经失效
+fn reinstall_hooks() {
+    let (keyboard, mouse) = (KEYBOARD_HOOK.read().is_some(), MOUSE_HOOK.read().is_some());
+    let mut result = Ok(());
+    if keyboard {
+        let _ = remove_keyboard_hook();
+        result = result.and(set_keyboard_hook());
+    }
+    if mouse {
+        let _ = remove_mouse_hook();
+        result = result.and(set_mouse_hook());
+    }
+    watchdog::reset();
+    if let Err(err) = result {
+        error!("failed to reinstall hooks: {}", err);
+        return;
+    }
+    info!("hooks reinstalled");
+    //钩子失效期间的按键抬起收不到
+    let _ = reconcile_keys_down();
+    dispatch_event(Event::HookReinstalled);
+}
+
+fn key_info(data: &KBDLLHOOKSTRUCT) -> KeyInfo {
+    KeyInfo {
+        vk_code: resolve_side(data.vkCode, data.scanCode, data.flags.0),
+        scan_code: data.scanCode,
+        time: data.time,
+        injected: data.flags.0 & LLKHF_INJECTED.0 != 0,
+        lower_il_injected: data.flags.0 & LLKHF_LOWER_IL_INJECTED.0 != 0,
+        modifiers: Modifiers::empty(),
+        is_repeat: false,
+        held_for: None,
+        is_numpad: is_numpad(data.scanCode, data.flags.0),
+        extra_info: data.dwExtraInfo,
+    }
+}
+
+/// 更新按下的按键集合，设置`is_repeat`和`held_for`
+fn update_keys_down(info: &mut KeyInfo, down: bool) {
+    let mut keys = KEYS_DOWN.write();
+    if down {
+        info.is_repeat = keys.contains_key(&info.vk_code);
+        keys.entry(info.vk_code).or_insert(info.time);
+    } else {
+        info.held_for = keys
+            .remove(&info.vk_code)
+            .map(|time| held_duration(time, info.time));
+    }
+}
+
+/// 更新按下的鼠标按键，按键抬起时返回按住的时间
+fn update_buttons_down(event: MouseEvent, time: u32) -> Option<Duration> {
+    let (button, action) = event.button()?;
+    let mut buttons = BUTTONS_DOWN.lock();
+    match action {
+        ButtonAction::Down => {
+            buttons.insert(button, time);
+            None
+        }
+        ButtonAction::Up => buttons
+            .remove(&button)
+            .map(|down| held_duration(down, time)),
+    }
+}
+
+fn held_duration(down: u32, up: u32) -> Duration {
+    //GetTickCount约49.7天回绕一次
+    Duration::from_millis(u32::wrapping_sub(up, down) as u64)
+}
+
+fn update_modifiers(vk: u32, down: bool) -> Modifiers {
+    let mut modifiers = MODIFIERS.write();
+    *modifiers = modifiers.update(vk, down);
+    *modifiers
+}
+
+fn dispatch_event(event: Event) -> Dispatch {
+    //过载时丢弃的事件不派发，仍然传递给下一个钩子
+    if !overload::admit(&event) {
+        return Dispatch::Pass;
+    }
+    let filter = *FILTER.read();
+    if let Some(filter) = filter {
+        //过滤器panic时照常派发
+        if call_guarded(|| filter(&event)) == Some(false) {
+            return Dispatch::Pass;
+        }
+    }
+    record::record(&event);
+    history::push(&event);
+    for (_, waiter) in NEXT_EVENT_WAITERS.lock().drain() {
+        let _ = waiter.try_send(event.clone());
+    }
+    {
+        let mut sender = EVENT_SENDER.lock();
+        if let Some(tx) = sender.as_ref() {
+            match tx.try_send(event.clone()) {
+                //通道已满，丢弃最新的事件
+                Ok(()) | Err(TrySendError::Full(_)) => (),
+                Err(TrySendError::Disconnected(_)) => {
+                    sender.take();
+                }
             }
         }
     }
-    CallNextHookEx(*KEYBOARD_HOOK.read().unwrap(), code, wparam, lparam)
+    #[cfg(feature = "tokio")]
+    stream::send(&event);
+    //处理CallbackSlow本身超时时不再派发，避免连续派发
+    let threshold = match event {
+        Event::CallbackSlow { .. } => None,
+        _ => CONFIG.read().slow_callback_threshold,
+    };
+    let start = Instant::now();
+    let mut dispatch = listener::dispatch(&event);
+    //启用工作线程时回调不能拦截事件
+    if let Some(event) = worker::try_send(event) {
+        if let Some(callback) = CALLBACK.lock().as_mut() {
+            if call_guarded(|| callback(event)) == Some(Dispatch::Block) {
+                dispatch = Dispatch::Block;
+            }
+        }
+    }
+    let duration = start.elapsed();
+    if threshold.is_some_and(|threshold| duration >= threshold) {
+        warn!(
+            "callback took {:?}, hooks exceeding LowLevelHooksTimeout are removed \
+             by Windows; consider HookConfig::callback_worker",
+            duration
+        );
+        dispatch_event(Event::CallbackSlow { duration });
+    }
+    if let Some(dropped) = overload::take_dropped() {
+        dispatch_event(Event::Overload { dropped });
+    }
+    dispatch
+}
+
+/// 调用用户回调并捕获panic，panic不能跨越extern "system"的钩子函数展开
+pub(crate) fn call_guarded<R>(f: impl FnOnce() -> R) -> Option<R> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)) {
+        Ok(ret) => Some(ret),
+        Err(err) => {
+            let msg = err
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| err.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            error!("callback panicked: {}", msg);
+            None
+        }
+    }
+}
+
+fn decode_mouse_event(msg: u32, data: &MSLLHOOKSTRUCT) -> Option<MouseEvent> {
+    let id = match msg {
+        WM_XBUTTONDOWN | WM_XBUTTONUP => (data.mouseData.0 & 0xFFFF0000) | msg,
+        _ => msg,
+    };
+    let event = MouseEvent::try_from(id as i32).ok()?;
+    if !CONFIG.read().swap_buttons.is_swapped() {
+        return Some(event);
+    }
+    Some(match event {
+        MouseEvent::MouseLeftButtonDown => MouseEvent::MouseRightButtonDown,
+        MouseEvent::MouseLeftButtonUp => MouseEvent::MouseRightButtonUp,
+        MouseEvent::MouseRightButtonDown => MouseEvent::MouseLeftButtonDown,
+        MouseEvent::MouseRightButtonUp => MouseEvent::MouseLeftButtonUp,
+        event => event,
+    })
+}
+
+fn mouse_info(data: &MSLLHOOKSTRUCT, event: &MouseEvent) -> MouseInfo {
+    let wheel_delta = match event {
+        MouseEvent::MouseWheel | MouseEvent::MouseHWheel => (data.mouseData.0 >> 16) as i16,
+        _ => 0,
+    };
+    MouseInfo {
+        point: Point {
+            x: data.pt.x,
+            y: data.pt.y,
+        },
+        time: data.time,
+        injected: data.flags & LLMHF_INJECTED != 0,
+        lower_il_injected: data.flags & LLMHF_LOWER_IL_INJECTED != 0,
+        wheel_delta,
+        wheel_direction: wheel::direction(*event, wheel_delta),
+        window: None,
+        client_pos: None,
+        held_for: None,
+        drag: None,
+        dx: 0,
+        dy: 0,
+        extra_info: data.dwExtraInfo,
+    }
+}
+
+/// 按`ignore_injected`/`ignore_own_injected`判断是否丢弃注入的事件
+fn is_ignored_injection(injected: bool, extra_info: usize) -> bool {
+    if !injected {
+        return false;
+    }
+    let config = CONFIG.read();
+    config.ignore_injected || config.ignore_own_injected && extra_info == INJECTED_EXTRA_INFO
+}
+
+/// 按`only_when_foreground`判断前台窗口是否允许派发事件
+fn is_foreground_allowed() -> bool {
+    match &CONFIG.read().only_when_foreground {
+        Some(target) => window::foreground_matches(target),
+        None => true,
+    }
+}
+
+fn is_kill_switch(vk: u32, modifiers: Modifiers) -> bool {
+    match CONFIG.read().kill_switch {
+        Some((required, key)) => vk == key && modifiers.contains(required),
+        None => false,
+    }
+}
+
+/// 按`mouse_move_deltas`计算相对上一次派发的移动事件的移动量
+fn move_delta(point: &Point) -> (i32, i32) {
+    let mut last = LAST_MOVE_POINT.lock();
+    //无论是否计算移动量都记录位置，`dedup_mouse_moves`需要比较
+    let delta = match last.as_ref() {
+        Some(last) if CONFIG.read().mouse_move_deltas => (point.x - last.x, point.y - last.y),
+        _ => (0, 0),
+    };
+    last.replace(point.clone());
+    delta
+}
+
+/// 按`report_mouse_moves`、`dedup_mouse_moves`和`mouse_move_throttle`判断是否派发本次移动事件
+fn should_report_move(info: &MouseInfo) -> bool {
+    let (throttle, dedup) = {
+        let config = CONFIG.read();
+        if !config.report_mouse_moves {
+            return false;
+        }
+        (config.mouse_move_throttle, config.dedup_mouse_moves)
+    };
+    //位置与上一次派发的移动事件相同，在节流之前判断，避免占用节流的时间间隔
+    if dedup && LAST_MOVE_POINT.lock().as_ref() == Some(&info.point) {
+        return false;
+    }
+    let throttle = match throttle {
+        Some(throttle) => throttle.as_millis() as u32,
+        None => return true,
+    };
+    let mut last = LAST_MOVE_TIME.lock();
+    if let Some(last) = *last {
+        if u32::wrapping_sub(info.time, last) < throttle {
+            return false;
+        }
+    }
+    last.replace(info.time);
+    true
+}
+
+unsafe extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let data = lparam.0 as *const KBDLLHOOKSTRUCT;
+    if !data.is_null() && process_key_input(code, wparam.0 as u32, &*data) {
+        return LRESULT(1);
+    }
+    CallNextHookEx(installed_hook(&KEYBOARD_HOOK), code, wparam, lparam)
+}
+
+/// 处理一次键盘输入，返回true时拦截该输入，线程钩子把收到的输入转换为低级钩子的格式后调用
+fn process_key_input(code: i32, msg: u32, data: &KBDLLHOOKSTRUCT) -> bool {
+    let mut dispatch = Dispatch::Pass;
+    idle::touch();
+    watchdog::touch(HookKind::Keyboard);
+    let down = match msg {
+        //普通键按下、系统键按下
+        0x100 | 0x104 => Some(true),
+        //普通键抬起、系统键抬起
+        0x101 | 0x105 => Some(false),
+        _ => None,
+    };
+    if let Some(down) = down {
+        //无论是否设置了回调都要更新修饰键状态
+        let mut info = key_info(data);
+        //紧急停止先于任何拦截和重映射判断，按键被拦截或改写时同样有效
+        let kill_modifiers = {
+            let mut modifiers = KILL_MODIFIERS.write();
+            *modifiers = modifiers.update(info.vk_code, down);
+            *modifiers
+        };
+        if down && is_kill_switch(info.vk_code, kill_modifiers) {
+            let _ = stop_hook();
+            return false;
+        }
+        //抖动产生的按键和suppress_next拦截的按键不更新按键状态，直接拦截；暂停时不判断
+        let source = debounce::Source::Key(info.vk_code);
+        if code >= 0
+            && !info.injected
+            && !is_paused()
+            && (debounce::is_bounce(source, down, info.time)
+                || suppress::should_suppress(Some(source), down))
+        {
+            return true;
+        }
+        //重映射的原始按键直接拦截，注入的目标按键再次进入钩子时按普通按键处理；
+        //暂停前按下的按键抬起时仍然要释放目标按键
+        if code >= 0 && (!down || !is_paused()) && remap::remap(info.vk_code, down, info.extra_info)
+        {
+            return true;
+        }
+        info.modifiers = update_modifiers(info.vk_code, down);
+        update_keys_down(&mut info, down);
+        stats::count_key(down, info.is_repeat, info.time);
+        //暂停时仍然跟踪按键状态，恢复后修饰键状态正确
+        let ignored = is_paused()
+            || is_ignored_injection(info.injected, info.extra_info)
+            || !is_foreground_allowed();
+        if !ignored {
+            chord::process_key(info.vk_code, down);
+            double_tap::process_key(info.vk_code, down, info.is_repeat, info.time);
+        }
+        //只有触发热键受暂停和忽略影响，热键拦截了按下的按键抬起时总是同样拦截
+        let hotkey = if !down {
+            hotkey::release_key(info.vk_code)
+        } else if ignored {
+            Dispatch::Pass
+        } else {
+            hotkey::process_key(info.vk_code, info.modifiers)
+        };
+        let suppress = ignored || info.is_repeat && CONFIG.read().suppress_repeats;
+        let blocked = !is_paused() && block::is_blocked(info.vk_code);
+        let event = if down {
+            KeyEvent::KeyPress(info)
+        } else {
+            KeyEvent::KeyUp(info)
+        };
+        if !suppress {
+            dispatch = dispatch_event(Event::KeyEvent(event));
+        }
+        if hotkey == Dispatch::Block || blocked {
+            dispatch = Dispatch::Block;
+        }
+    }
+    code >= 0 && dispatch == Dispatch::Block
 }
 
 unsafe extern "system" fn mouse_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
-    if let Ok(callback) = CALLBACK.read() {
-        if let Some(callback) = callback.as_ref() {
-            let data = lparam.0 as *const MSLLHOOKSTRUCT;
-            if !data.is_null() {
-                if let Ok(mouse_event) = MouseEvent::try_from(wparam.0 as i32) {
-                    let point = Point {
-                        x: (*data).pt.x,
-                        y: (*data).pt.y,
-                    };
-                    let _ = callback(Event::MouseEvent((mouse_event, point)));
+    let data = lparam.0 as *const MSLLHOOKSTRUCT;
+    if !data.is_null() && process_mouse_input(code, wparam.0 as u32, &*data) {
+        return LRESULT(1);
+    }
+    CallNextHookEx(installed_hook(&MOUSE_HOOK), code, wparam, lparam)
+}
+
+/// 处理一次鼠标输入，返回true时拦截该输入，线程钩子把收到的输入转换为低级钩子的格式后调用
+fn process_mouse_input(code: i32, msg: u32, data: &MSLLHOOKSTRUCT) -> bool {
+    let mut dispatch = Dispatch::Pass;
+    idle::touch();
+    watchdog::touch(HookKind::Mouse);
+    if let Some(mouse_event) = decode_mouse_event(msg, data) {
+        let mut info = mouse_info(data, &mouse_event);
+        //暂停时不做防抖和suppress_next拦截
+        if code >= 0 && !info.injected && !is_paused() {
+            let blocked = match mouse_event.button() {
+                Some((button, action)) => {
+                    let source = debounce::Source::Button(button);
+                    let down = action == ButtonAction::Down;
+                    debounce::is_bounce(source, down, info.time)
+                        || suppress::should_suppress(Some(source), down)
                 }
+                None if matches!(
+                    mouse_event,
+                    MouseEvent::MouseWheel | MouseEvent::MouseHWheel
+                ) =>
+                {
+                    suppress::should_suppress(None, true)
+                }
+                None => false,
+            };
+            if blocked {
+                return true;
             }
         }
+        stats::count_mouse(mouse_event, &info);
+        info.held_for = update_buttons_down(mouse_event, info.time);
+        if is_paused()
+            || is_ignored_injection(info.injected, info.extra_info)
+            || !is_foreground_allowed()
+        {
+            return false;
+        }
+        //拖动需要看到所有移动事件，在过滤移动事件之前处理
+        let drag = drag::detect(mouse_event, &info);
+        if mouse_event != MouseEvent::MouseMove || should_report_move(&info) {
+            if mouse_event == MouseEvent::MouseMove {
+                (info.dx, info.dy) = move_delta(&info.point);
+            }
+            if matches!(mouse_event.button(), Some((_, ButtonAction::Down)))
+                && CONFIG.read().window_info
+            {
+                info.window = window::window_at(&info.point);
+            }
+            if mouse_event.button().is_some() && CONFIG.read().client_pos {
+                info.client_pos = window::client_pos(&info.point);
+            }
+            let double_click = double_click::detect(mouse_event, &info);
+            dispatch = dispatch_event(Event::MouseEvent((mouse_event, info.clone())));
+            if mouse_event == MouseEvent::MouseMove && !CONFIG.read().block_mouse_move {
+                dispatch = Dispatch::Pass;
+            }
+            //双击事件在原始按下事件之后派发，拦截双击事件没有效果
+            if let Some(double_click) = double_click {
+                dispatch_event(Event::MouseEvent((double_click, info.clone())));
+            }
+            //高精度滚轮每次的滚动量可能不足一格，累计满一格后额外派发整格事件
+            if let Some((notch_event, delta)) = wheel::detect(mouse_event, info.wheel_delta) {
+                let info = MouseInfo {
+                    wheel_delta: delta,
+                    wheel_direction: wheel::direction(notch_event, delta),
+                    ..info.clone()
+                };
+                dispatch_event(Event::MouseEvent((notch_event, info)));
+            }
+        }
+        //拖动事件同样在原始事件之后派发
+        if let Some((drag_event, drag)) = drag {
+            info.window = None;
+            info.client_pos = None;
+            info.drag = Some(drag);
+            dispatch_event(Event::MouseEvent((drag_event, info)));
+        }
     }
-    CallNextHookEx(*MOUSE_HOOK.read().unwrap(), code, wparam, lparam)
+    code >= 0 && dispatch == Dispatch::Block
 }
 
-fn set_keyboard_hook(f: HookFn) -> Result<()> {
-    let mut kbd_hook = KEYBOARD_HOOK.write().map_err(|err| anyhow!("{:?}", err))?;
-    unsafe {
-        kbd_hook.replace(SetWindowsHookExW(
-            WH_KEYBOARD_LL,
-            HOOKPROC::Some(f),
-            HINSTANCE::default(),
-            0,
-        )?);
+/// 读取已安装的钩子句柄，没有安装时返回空句柄(CallNextHookEx允许传入空句柄)
+fn installed_hook(hook: &RwLock<Option<HHOOK>>) -> HHOOK {
+    hook.read().unwrap_or_default()
+}
+
+fn set_keyboard_hook() -> Result<()> {
+    let mut kbd_hook = KEYBOARD_HOOK.write();
+    if kbd_hook.is_some() {
+        return Err(KbdmsError::AlreadyHooked);
+    }
+    let (id, f, thread): (_, HookFn, _) = match CONFIG.read().target_thread {
+        Some(thread) => (WH_KEYBOARD, thread_hook::keyboard_proc, thread),
+        None => (WH_KEYBOARD_LL, keyboard_hook_proc, 0),
+    };
+    //线程属于当前进程、钩子函数在当前模块中时hMod为空
+    let hook = unsafe { SetWindowsHookExW(id, HOOKPROC::Some(f), HINSTANCE::default(), thread) }
+        .map_err(KbdmsError::HookInstall);
+    match hook {
+        Ok(hook) => {
+            debug!("keyboard hook installed");
+            kbd_hook.replace(hook);
+            Ok(())
+        }
+        Err(err) => {
+            error!("failed to install keyboard hook: {}", err);
+            Err(err)
+        }
     }
-    Ok(())
 }
 
-fn set_mouse_hook(f: HookFn) -> Result<()> {
-    let mut ms_hook = MOUSE_HOOK.write().map_err(|err| anyhow!("{:?}", err))?;
-    unsafe {
-        ms_hook.replace(SetWindowsHookExW(
-            WH_MOUSE_LL,
-            HOOKPROC::Some(f),
-            HINSTANCE::default(),
-            0,
-        )?);
+fn set_mouse_hook() -> Result<()> {
+    let mut ms_hook = MOUSE_HOOK.write();
+    if ms_hook.is_some() {
+        return Err(KbdmsError::AlreadyHooked);
+    }
+    let (id, f, thread): (_, HookFn, _) = match CONFIG.read().target_thread {
+        Some(thread) => (WH_MOUSE, thread_hook::mouse_proc, thread),
+        None => (WH_MOUSE_LL, mouse_hook_proc, 0),
+    };
+    //线程属于当前进程、钩子函数在当前模块中时hMod为空
+    let hook = unsafe { SetWindowsHookExW(id, HOOKPROC::Some(f), HINSTANCE::default(), thread) }
+        .map_err(KbdmsError::HookInstall);
+    match hook {
+        Ok(hook) => {
+            debug!("mouse hook installed");
+            ms_hook.replace(hook);
+            Ok(())
+        }
+        Err(err) => {
+            error!("failed to install mouse hook: {}", err);
+            Err(err)
+        }
     }
-    Ok(())
 }
 
 fn remove_keyboard_hook() -> Result<()> {
-    if let Some(hook) = KEYBOARD_HOOK
-        .read()
-        .map_err(|err| anyhow!("{:?}", err))?
-        .as_ref()
-    {
-        unsafe {
-            let _ = UnhookWindowsHookEx(*hook);
+    if let Some(hook) = KEYBOARD_HOOK.write().take() {
+        match unsafe { UnhookWindowsHookEx(hook) }.ok() {
+            Ok(()) => debug!("keyboard hook removed"),
+            Err(err) => warn!("failed to remove keyboard hook: {}", err),
         }
     }
     Ok(())
 }
 
 fn remove_mouse_hook() -> Result<()> {
-    if let Some(hook) = MOUSE_HOOK
-        .read()
-        .map_err(|err| anyhow!("{:?}", err))?
-        .as_ref()
-    {
-        unsafe {
-            let _ = UnhookWindowsHookEx(*hook);
+    if let Some(hook) = MOUSE_HOOK.write().take() {
+        match unsafe { UnhookWindowsHookEx(hook) }.ok() {
+            Ok(()) => debug!("mouse hook removed"),
+            Err(err) => warn!("failed to remove mouse hook: {}", err),
         }
     }
     Ok(())
@@ -200,18 +1587,764 @@ fn remove_mouse_hook() -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
     use std::time::Duration;
+    use windows::Win32::UI::WindowsAndMessaging::{
+        LLKHF_EXTENDED, MOUSEHOOKSTRUCTEX, WM_KEYDOWN, WM_KEYUP, WM_XBUTTONDBLCLK,
+    };
+
+    //钩子是全局状态，测试需要串行执行
+    static SERIAL: Mutex<()> = Mutex::new(());
 
     #[test]
     fn it_works() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
         let ret = start_hook_async(true, false);
         println!("{:?}", ret);
         let ret = set_hook_callback(|e| {
             println!("{:?}", e);
+            Dispatch::Pass
         });
         println!("{:?}", ret);
 
         std::thread::sleep(Duration::from_secs(10));
         let _ = stop_hook();
     }
+
+    #[test]
+    fn stop_hook_wakes_idle_loop() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let handle = start_hook_async(false, false);
+        std::thread::sleep(Duration::from_millis(200));
+        stop_hook().unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(1);
+        while !handle.is_finished() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "hook thread did not exit"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(handle.join().unwrap().is_ok());
+    }
+    #[test]
+    fn stop_and_join_ignores_failed_handle() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let running = start_hook_async(false, false);
+        //第二次启动失败，停止它的句柄不能停掉正在运行的钩子
+        let failed = start_hook_async(false, false);
+        assert!(matches!(
+            stop_and_join(failed),
+            Err(KbdmsError::AlreadyRunning)
+        ));
+        assert!(is_running());
+        assert!(stop_and_join(running).is_ok());
+        assert!(!is_running());
+    }
+    #[test]
+    fn next_event_timeout_unregisters_waiter() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        //钩子没有运行时不注册
+        assert!(next_event(Some(Duration::from_millis(1))).is_none());
+        assert!(NEXT_EVENT_WAITERS.lock().is_empty());
+        let handle = start_hook_async(false, false);
+        std::thread::sleep(Duration::from_millis(100));
+        for _ in 0..3 {
+            assert!(next_event(Some(Duration::from_millis(1))).is_none());
+        }
+        assert!(NEXT_EVENT_WAITERS.lock().is_empty());
+        stop_and_join(handle).unwrap();
+    }
+    #[test]
+    fn event_time_is_non_decreasing() {
+        //钩子结构体中的时间原样保存到事件，包括GetTickCount回绕前后的时间
+        let times = [u32::MAX - 20, u32::MAX - 5, u32::MAX, 0, 15];
+        let events: Vec<Event> = times
+            .iter()
+            .enumerate()
+            .map(|(i, &time)| {
+                if i % 2 == 0 {
+                    let kbd = KBDLLHOOKSTRUCT {
+                        time,
+                        ..Default::default()
+                    };
+                    Event::KeyEvent(KeyEvent::KeyPress(key_info(&kbd)))
+                } else {
+                    let ms = MSLLHOOKSTRUCT {
+                        time,
+                        ..Default::default()
+                    };
+                    let info = mouse_info(&ms, &MouseEvent::MouseMove);
+                    Event::MouseEvent((MouseEvent::MouseMove, info))
+                }
+            })
+            .collect();
+        let got: Vec<u32> = events.iter().map(Event::time).collect();
+        assert_eq!(got, times);
+        //按回绕后的差值比较先后
+        assert!(got
+            .windows(2)
+            .all(|w| u32::wrapping_sub(w[1], w[0]) as i32 >= 0));
+        assert_eq!(held_duration(u32::MAX - 5, 15), Duration::from_millis(21));
+    }
+    #[test]
+    fn injected_flags() {
+        let mut kbd = KBDLLHOOKSTRUCT::default();
+        let info = key_info(&kbd);
+        assert!(!info.injected && !info.lower_il_injected);
+        kbd.flags = LLKHF_INJECTED;
+        let info = key_info(&kbd);
+        assert!(info.injected && !info.lower_il_injected);
+        kbd.flags.0 = LLKHF_INJECTED.0 | LLKHF_LOWER_IL_INJECTED.0;
+        let info = key_info(&kbd);
+        assert!(info.injected && info.lower_il_injected);
+
+        let mut ms = MSLLHOOKSTRUCT::default();
+        let info = mouse_info(&ms, &MouseEvent::MouseMove);
+        assert!(!info.injected && !info.lower_il_injected);
+        ms.flags = LLMHF_INJECTED;
+        let info = mouse_info(&ms, &MouseEvent::MouseMove);
+        assert!(info.injected && !info.lower_il_injected);
+        ms.flags = LLMHF_INJECTED | LLMHF_LOWER_IL_INJECTED;
+        let info = mouse_info(&ms, &MouseEvent::MouseMove);
+        assert!(info.injected && info.lower_il_injected);
+    }
+    #[test]
+    fn decode_xbuttons() {
+        let mut ms = MSLLHOOKSTRUCT::default();
+        ms.mouseData.0 = 1 << 16;
+        assert_eq!(
+            decode_mouse_event(WM_XBUTTONDOWN, &ms),
+            Some(MouseEvent::MouseXButton1Down)
+        );
+        assert_eq!(
+            decode_mouse_event(WM_XBUTTONUP, &ms),
+            Some(MouseEvent::MouseXButton1Up)
+        );
+        ms.mouseData.0 = 2 << 16;
+        assert_eq!(
+            decode_mouse_event(WM_XBUTTONDOWN, &ms),
+            Some(MouseEvent::MouseXButton2Down)
+        );
+        assert_eq!(
+            decode_mouse_event(WM_XBUTTONUP, &ms),
+            Some(MouseEvent::MouseXButton2Up)
+        );
+        ms.mouseData.0 = 0;
+        assert_eq!(decode_mouse_event(WM_XBUTTONDOWN, &ms), None);
+    }
+    #[test]
+    fn display_mouse_event() {
+        let info = MouseInfo {
+            point: Point { x: 1024, y: 768 },
+            ..Default::default()
+        };
+        let event = Event::MouseEvent((MouseEvent::MouseMove, info.clone()));
+        assert_eq!(event.to_string(), "MouseMove(1024, 768)");
+        let event = Event::MouseEvent((
+            MouseEvent::MouseWheel,
+            MouseInfo {
+                wheel_delta: -120,
+                ..info
+            },
+        ));
+        assert_eq!(event.to_string(), "MouseWheel(1024, 768, -120)");
+    }
+    #[test]
+    fn json_output() {
+        let event = Event::MouseEvent((
+            MouseEvent::MouseLeftButtonDown,
+            MouseInfo {
+                point: Point { x: 10, y: 20 },
+                time: 5,
+                ..Default::default()
+            },
+        ));
+        assert_eq!(
+            event.to_json(),
+            r#"{"type":"mouse","event":"left_down","x":10,"y":20,"delta":0,"time":5,"injected":false}"#
+        );
+        assert_eq!(Event::HookStopped.to_json(), r#"{"type":"hook_stopped"}"#);
+        let slow = Event::CallbackSlow {
+            duration: Duration::from_millis(250),
+        };
+        assert_eq!(
+            slow.to_json(),
+            r#"{"type":"callback_slow","duration_ms":250}"#
+        );
+    }
+    #[test]
+    fn numpad_scan_codes() {
+        let extended = LLKHF_EXTENDED.0;
+        for (scan_code, flags, numpad) in [
+            //主键盘5、小键盘5
+            (0x06, 0, false),
+            (0x4C, 0, true),
+            //关闭NumLock时的小键盘7与独立的Home
+            (0x47, 0, true),
+            (0x47, extended, false),
+            //小键盘乘号与PrintScreen
+            (0x37, 0, true),
+            (0x37, extended, false),
+            //小键盘除号与主键盘/
+            (0x35, extended, true),
+            (0x35, 0, false),
+            //小键盘Enter与主键盘Enter
+            (0x1C, extended, true),
+            (0x1C, 0, false),
+        ] {
+            assert_eq!(
+                is_numpad(scan_code, flags),
+                numpad,
+                "{:#X} {}",
+                scan_code,
+                flags
+            );
+        }
+    }
+    #[test]
+    fn button_round_trip() {
+        for button in [
+            MouseButton::Left,
+            MouseButton::Right,
+            MouseButton::Middle,
+            MouseButton::X1,
+            MouseButton::X2,
+        ] {
+            for action in [ButtonAction::Down, ButtonAction::Up] {
+                let event = MouseEvent::from_button(button, action);
+                assert_eq!(event.button(), Some((button, action)));
+            }
+        }
+        assert_eq!(MouseEvent::MouseMove.button(), None);
+    }
+    #[test]
+    fn middle_button_down_discriminant() {
+        assert_eq!(
+            MouseEvent::try_from(0x207),
+            Ok(MouseEvent::MouseMiddleButtonDown)
+        );
+        assert!(MouseEvent::try_from(0x2b).is_err());
+    }
+    #[test]
+    fn mouse_event_try_from_wm_messages() {
+        use MouseEvent::*;
+        let x1 = (XBUTTON1.0 << 16) as i32;
+        let x2 = (XBUTTON2.0 << 16) as i32;
+        let cases = [
+            (WM_MOUSEMOVE as i32, MouseMove),
+            (WM_LBUTTONDOWN as i32, MouseLeftButtonDown),
+            (WM_LBUTTONUP as i32, MouseLeftButtonUp),
+            (WM_LBUTTONDBLCLK as i32, MouseLeftDoubleClick),
+            (WM_RBUTTONDOWN as i32, MouseRightButtonDown),
+            (WM_RBUTTONUP as i32, MouseRightButtonUp),
+            (WM_RBUTTONDBLCLK as i32, MouseRightDoubleClick),
+            (WM_MBUTTONDOWN as i32, MouseMiddleButtonDown),
+            (WM_MBUTTONUP as i32, MouseMiddleButtonUp),
+            (WM_MBUTTONDBLCLK as i32, MouseMiddleDoubleClick),
+            (WM_MOUSEWHEEL as i32, MouseWheel),
+            (WM_MOUSEHWHEEL as i32, MouseHWheel),
+            (x1 | WM_XBUTTONDOWN as i32, MouseXButton1Down),
+            (x1 | WM_XBUTTONUP as i32, MouseXButton1Up),
+            (x2 | WM_XBUTTONDOWN as i32, MouseXButton2Down),
+            (x2 | WM_XBUTTONUP as i32, MouseXButton2Up),
+        ];
+        for (msg, event) in cases {
+            assert_eq!(MouseEvent::try_from(msg), Ok(event), "message {:#x}", msg);
+        }
+        //没有区分侧键的WM_XBUTTON*和WM_XBUTTONDBLCLK不对应任何事件
+        assert!(MouseEvent::try_from(WM_XBUTTONDOWN as i32).is_err());
+        assert!(MouseEvent::try_from(WM_XBUTTONUP as i32).is_err());
+        assert!(MouseEvent::try_from(WM_XBUTTONDBLCLK as i32).is_err());
+    }
+    #[test]
+    fn event_accessors() {
+        let key = Event::KeyEvent(KeyEvent::KeyPress(KeyInfo {
+            vk_code: vk::A,
+            scan_code: 0x1E,
+            ..Default::default()
+        }));
+        assert!(key.is_key_press());
+        assert!(!key.is_key_up());
+        assert_eq!(key.key_code(), Some(vk::A));
+        assert_eq!(key.as_key_press(), Some(vk::A));
+        assert_eq!(key.key(), Some((true, vk::A)));
+        assert_eq!(key.source(), Some(EventSource::Physical));
+        assert_eq!(Event::HookStarted.source(), None);
+        assert_eq!(
+            EventSource::from_flags(true, true),
+            EventSource::InjectedLowerIL
+        );
+        assert_eq!(EventSource::from_flags(true, false), EventSource::Injected);
+        assert!(!key.is_mouse_move());
+        assert_eq!(key.mouse_position(), None);
+        assert_eq!(key.as_click(), None);
+
+        let point = Point { x: 10, y: 20 };
+        let mouse = |event| {
+            Event::MouseEvent((
+                event,
+                MouseInfo {
+                    point: point.clone(),
+                    ..Default::default()
+                },
+            ))
+        };
+        let up = mouse(MouseEvent::MouseRightButtonUp);
+        assert_eq!(up.key_code(), None);
+        assert_eq!(up.mouse_position(), Some(&point));
+        assert_eq!(up.as_click(), Some((MouseButton::Right, &point)));
+        assert_eq!(mouse(MouseEvent::MouseRightButtonDown).as_click(), None);
+        assert_eq!(up.key(), None);
+        assert!(!up.is_mouse_move());
+        assert!(mouse(MouseEvent::MouseMove).is_mouse_move());
+        assert!(!mouse(MouseEvent::DragMove).is_mouse_move());
+    }
+    #[test]
+    fn suppress_next_counts_presses() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let key = debounce::Source::Key(vk::A);
+        suppress_next(2, Duration::from_secs(10)).unwrap();
+        assert!(suppress::should_suppress(Some(key), true));
+        assert!(suppress::should_suppress(Some(key), false));
+        //滚轮没有抬起事件
+        assert!(suppress::should_suppress(None, true));
+        assert!(!suppress::should_suppress(Some(key), true));
+        assert!(!suppress::should_suppress(Some(key), false));
+        suppress_next(1, Duration::ZERO).unwrap();
+        assert!(!suppress::should_suppress(Some(key), true));
+    }
+    #[test]
+    fn suppress_next_skips_held_key_repeats() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let key = debounce::Source::Key(vk::LSHIFT);
+        KEYS_DOWN.write().clear();
+        //调用suppress_next前已经按住Shift
+        KEYS_DOWN.write().insert(vk::LSHIFT, 0);
+        suppress_next(1, Duration::from_secs(10)).unwrap();
+        assert!(!suppress::should_suppress(Some(key), true));
+        assert!(!suppress::should_suppress(Some(key), false));
+        KEYS_DOWN.write().clear();
+        //被拦截的按键的重复按下同样拦截，不计入次数
+        assert!(suppress::should_suppress(Some(key), true));
+        assert!(suppress::should_suppress(Some(key), true));
+        assert!(suppress::should_suppress(Some(key), false));
+        suppress_next(0, Duration::ZERO).unwrap();
+    }
+    #[test]
+    fn wheel_notches_carry_remainder() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new().wheel_notches(true);
+        wheel::reset();
+        assert_eq!(wheel::detect(MouseEvent::MouseWheel, 40), None);
+        assert_eq!(wheel::detect(MouseEvent::MouseWheel, 40), None);
+        assert_eq!(
+            wheel::detect(MouseEvent::MouseWheel, 50),
+            Some((MouseEvent::MouseWheelNotch, 120))
+        );
+        assert_eq!(
+            wheel::detect(MouseEvent::MouseWheel, 230),
+            Some((MouseEvent::MouseWheelNotch, 240))
+        );
+        assert_eq!(wheel::detect(MouseEvent::MouseHWheel, -60), None);
+        *CONFIG.write() = HookConfig::new();
+        wheel::reset();
+    }
+    #[test]
+    fn debounce_drops_only_fast_presses() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new().debounce(Duration::from_millis(30));
+        debounce::reset();
+        let key = debounce::Source::Key(vk::A);
+        assert!(!debounce::is_bounce(key, true, 1000));
+        //自动重复
+        assert!(!debounce::is_bounce(key, true, 1010));
+        assert!(!debounce::is_bounce(key, false, 1015));
+        //抖动的按下及其抬起
+        assert!(debounce::is_bounce(key, true, 1020));
+        assert!(debounce::is_bounce(key, false, 1025));
+        assert!(!debounce::is_bounce(key, true, 1040));
+        *CONFIG.write() = HookConfig::new();
+        debounce::reset();
+    }
+    #[test]
+    fn debounce_releases_key_held_after_bounce() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new().debounce(Duration::from_millis(30));
+        debounce::reset();
+        let key = debounce::Source::Key(vk::A);
+        assert!(!debounce::is_bounce(key, true, 1000));
+        assert!(!debounce::is_bounce(key, false, 1010));
+        //窗口内再次按下并按住
+        assert!(debounce::is_bounce(key, true, 1020));
+        //窗口之后的自动重复有效，抬起也要传递
+        assert!(!debounce::is_bounce(key, true, 1060));
+        assert!(!debounce::is_bounce(key, false, 1070));
+        *CONFIG.write() = HookConfig::new();
+        debounce::reset();
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let event = Event::MouseEvent((
+            MouseEvent::MouseWheel,
+            MouseInfo {
+                point: Point { x: 10, y: -20 },
+                time: 1234,
+                wheel_delta: -120,
+                ..Default::default()
+            },
+        ));
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"MouseWheel\""));
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+
+        let event = Event::KeyEvent(KeyEvent::KeyPress(KeyInfo {
+            vk_code: 0x41,
+            scan_code: 0x1E,
+            time: 1234,
+            injected: true,
+            modifiers: Modifiers::LSHIFT | Modifiers::SHIFT,
+            extra_info: INJECTED_EXTRA_INFO,
+            ..Default::default()
+        }));
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+    }
+    #[test]
+    fn panicking_callback_does_not_break_dispatch() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        set_hook_callback_boxed(Box::new(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("first event panics");
+            }
+            Dispatch::Pass
+        }))
+        .unwrap();
+        let handle = start_hook_async(false, true);
+        std::thread::sleep(Duration::from_millis(200));
+        //F24
+        tap_key(0x87).unwrap();
+        tap_key(0x87).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        stop_hook().unwrap();
+        assert!(handle.join().unwrap().is_ok());
+        assert!(calls.load(Ordering::SeqCst) >= 4);
+    }
+    #[test]
+    fn second_start_is_rejected() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let first = start_hook_async(false, true);
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(is_running());
+
+        let second = start_hook_async(false, true);
+        assert!(matches!(
+            second.join().unwrap(),
+            Err(KbdmsError::AlreadyRunning)
+        ));
+        assert!(is_running());
+
+        stop_hook().unwrap();
+        assert!(first.join().unwrap().is_ok());
+        assert!(!is_running());
+    }
+    #[test]
+    fn next_event_returns_when_hook_stops() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        assert_eq!(next_event(None), None);
+
+        let handle = start_hook_async(false, true);
+        std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(200));
+            stop_hook().unwrap();
+        });
+        let start = Instant::now();
+        //只安装了键盘钩子，测试期间没有按键时等待会因为钩子停止而结束
+        while next_event(None).is_some() {}
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(handle.join().unwrap().is_ok());
+    }
+    #[test]
+    fn swap_buttons_decodes_logical_buttons() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let ms = MSLLHOOKSTRUCT::default();
+        *CONFIG.write() = HookConfig::new().swap_buttons(ButtonSwap::Always);
+        assert_eq!(
+            decode_mouse_event(0x201, &ms),
+            Some(MouseEvent::MouseRightButtonDown)
+        );
+        assert_eq!(
+            decode_mouse_event(0x205, &ms),
+            Some(MouseEvent::MouseLeftButtonUp)
+        );
+        assert_eq!(
+            decode_mouse_event(0x207, &ms),
+            Some(MouseEvent::MouseMiddleButtonDown)
+        );
+        *CONFIG.write() = HookConfig::new();
+        assert_eq!(
+            decode_mouse_event(0x201, &ms),
+            Some(MouseEvent::MouseLeftButtonDown)
+        );
+    }
+    #[test]
+    fn overload_drops_moves_before_wheel() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new().overload_limit(Some(2));
+        overload::reset();
+        let ms = MSLLHOOKSTRUCT::default();
+        let mouse = |event| Event::MouseEvent((event, mouse_info(&ms, &event)));
+        assert!(overload::admit(&mouse(MouseEvent::MouseMove)));
+        assert!(overload::admit(&mouse(MouseEvent::MouseMove)));
+        assert!(!overload::admit(&mouse(MouseEvent::MouseMove)));
+        assert!(overload::admit(&mouse(MouseEvent::MouseWheel)));
+        assert!(overload::admit(&mouse(MouseEvent::MouseLeftButtonDown)));
+        assert!(!overload::admit(&mouse(MouseEvent::MouseWheel)));
+        assert!(overload::admit(&mouse(MouseEvent::MouseLeftButtonUp)));
+        assert_eq!(overload::take_dropped(), None);
+        *CONFIG.write() = HookConfig::new();
+        overload::reset();
+    }
+    #[test]
+    fn wheel_direction_from_delta() {
+        use wheel::direction;
+        assert_eq!(
+            direction(MouseEvent::MouseWheel, 120),
+            Some(WheelDirection::Up)
+        );
+        assert_eq!(
+            direction(MouseEvent::MouseWheel, -30),
+            Some(WheelDirection::Down)
+        );
+        assert_eq!(
+            direction(MouseEvent::MouseHWheel, 120),
+            Some(WheelDirection::Right)
+        );
+        assert_eq!(
+            direction(MouseEvent::MouseHWheelNotch, -240),
+            Some(WheelDirection::Left)
+        );
+        assert_eq!(direction(MouseEvent::MouseWheel, 0), None);
+        assert_eq!(direction(MouseEvent::MouseMove, 120), None);
+    }
+    #[test]
+    fn dedup_drops_repeated_move_positions() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new().dedup_mouse_moves(true);
+        LAST_MOVE_POINT.lock().take();
+        let mut ms = MSLLHOOKSTRUCT::default();
+        ms.pt.x = 5;
+        let info = mouse_info(&ms, &MouseEvent::MouseMove);
+        assert!(should_report_move(&info));
+        move_delta(&info.point);
+        assert!(!should_report_move(&info));
+        ms.pt.x = 6;
+        assert!(should_report_move(&mouse_info(&ms, &MouseEvent::MouseMove)));
+        *CONFIG.write() = HookConfig::new();
+        LAST_MOVE_POINT.lock().take();
+    }
+
+    #[test]
+    fn blocked_mouse_button_skips_next_hook() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new();
+        set_hook_callback(|_| Dispatch::Block).unwrap();
+        let ms = MSLLHOOKSTRUCT::default();
+        let call = |msg: u32| unsafe {
+            mouse_hook_proc(0, WPARAM(msg as usize), LPARAM(&ms as *const _ as isize))
+        };
+        //被拦截的按下直接返回1，没有调用CallNextHookEx
+        assert_eq!(call(WM_LBUTTONDOWN), LRESULT(1));
+        assert_eq!(call(WM_LBUTTONUP), LRESULT(1));
+        //默认不拦截移动事件
+        assert_ne!(call(WM_MOUSEMOVE), LRESULT(1));
+        CALLBACK.lock().take();
+        BUTTONS_DOWN.lock().clear();
+        LAST_MOVE_POINT.lock().take();
+    }
+
+    #[test]
+    fn thread_hook_decodes_message_params() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new();
+        history::set_capacity(8);
+        KEYS_DOWN.write().clear();
+        BUTTONS_DOWN.lock().clear();
+        unsafe {
+            //扫描码0x1E，重复次数1；抬起时设置30、31位
+            thread_hook::keyboard_proc(0, WPARAM(vk::A as usize), LPARAM(0x001E_0001));
+            thread_hook::keyboard_proc(0, WPARAM(vk::A as usize), LPARAM(0xC01E_0001u32 as isize));
+            //窗口类带CS_DBLCLKS时的双击消息还原为按下
+            let ms = MOUSEHOOKSTRUCTEX::default();
+            let lparam = LPARAM(&ms as *const _ as isize);
+            thread_hook::mouse_proc(0, WPARAM(WM_LBUTTONDBLCLK as usize), lparam);
+            thread_hook::mouse_proc(0, WPARAM(WM_LBUTTONUP as usize), lparam);
+        }
+        let events = recent_events(4);
+        assert_eq!(events[0].as_key_press(), Some(vk::A));
+        assert_eq!(events[0].key_info().unwrap().scan_code, 0x1E);
+        assert!(events[1].is_key_up());
+        assert!(events[1].key_info().unwrap().held_for.is_some());
+        assert!(matches!(
+            events[2].mouse_button(),
+            Some((MouseButton::Left, ButtonAction::Down, _))
+        ));
+        assert!(matches!(
+            events[3].mouse_button(),
+            Some((MouseButton::Left, ButtonAction::Up, _))
+        ));
+        history::set_capacity(0);
+        BUTTONS_DOWN.lock().clear();
+        *MODIFIERS.write() = Modifiers::empty();
+    }
+    #[test]
+    fn replay_delay_ignores_backward_timestamps() {
+        use record::replay_delay;
+        assert_eq!(replay_delay(1000, 1500, 1.0), Duration::from_millis(500));
+        assert_eq!(replay_delay(1000, 1500, 2.0), Duration::from_millis(250));
+        //时间戳倒退时不等待，而不是回绕成约49天
+        assert_eq!(replay_delay(1500, 1000, 1.0), Duration::ZERO);
+        //GetTickCount回绕
+        assert_eq!(
+            replay_delay(u32::MAX - 149, 100, 1.0),
+            Duration::from_millis(250)
+        );
+        assert_eq!(replay_delay(0, 1000, f32::MIN_POSITIVE), Duration::MAX);
+    }
+    #[test]
+    fn kill_switch_fires_for_suppressed_keys() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new().kill_switch(Some((Modifiers::CONTROL, vk::F12)));
+        *KILL_MODIFIERS.write() = Modifiers::empty();
+        EXIT.store(false, Ordering::Release);
+        KEYS_DOWN.write().clear();
+        //Ctrl和F12都被suppress_next拦截，紧急停止仍然生效
+        suppress_next(2, Duration::from_secs(10)).unwrap();
+        let ctrl = KBDLLHOOKSTRUCT {
+            vkCode: vk::LCONTROL,
+            ..Default::default()
+        };
+        let f12 = KBDLLHOOKSTRUCT {
+            vkCode: vk::F12,
+            ..Default::default()
+        };
+        assert!(process_key_input(0, WM_KEYDOWN, &ctrl));
+        assert!(!EXIT.load(Ordering::Acquire));
+        assert!(!process_key_input(0, WM_KEYDOWN, &f12));
+        assert!(EXIT.load(Ordering::Acquire));
+        suppress_next(0, Duration::ZERO).unwrap();
+        EXIT.store(false, Ordering::Release);
+        *KILL_MODIFIERS.write() = Modifiers::empty();
+        KEYS_DOWN.write().clear();
+        *CONFIG.write() = HookConfig::new();
+    }
+    #[test]
+    fn paused_hook_skips_suppress_next() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new();
+        KEYS_DOWN.write().clear();
+        suppress_next(1, Duration::from_secs(10)).unwrap();
+        let a = KBDLLHOOKSTRUCT {
+            vkCode: vk::A,
+            ..Default::default()
+        };
+        pause();
+        assert!(!process_key_input(0, WM_KEYDOWN, &a));
+        assert!(!process_key_input(0, WM_KEYUP, &a));
+        resume();
+        //暂停期间的按键不消耗拦截次数
+        assert!(process_key_input(0, WM_KEYDOWN, &a));
+        assert!(process_key_input(0, WM_KEYUP, &a));
+        suppress_next(0, Duration::ZERO).unwrap();
+        KEYS_DOWN.write().clear();
+    }
+    #[test]
+    fn watchdog_compares_tick_counts_across_wrap() {
+        use watchdog::saw_input;
+        assert!(saw_input(1500, 1000));
+        assert!(!saw_input(5000, 1000));
+        //钩子函数在最后一次输入之后被调用
+        assert!(saw_input(1000, 1200));
+        //GetTickCount回绕
+        assert!(saw_input(100, u32::MAX - 100));
+        assert!(!saw_input(2000, u32::MAX - 100));
+    }
+    #[test]
+    fn callback_worker_clamps_zero_capacity() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        worker::start(0).unwrap();
+        assert_eq!(worker::backlog(), Some((0, 1)));
+        //容量为0的同步通道没有缓冲，这里应当能够入队
+        assert!(worker::try_send(Event::HookStarted).is_none());
+        assert_eq!(dropped_callback_events(), 0);
+        worker::stop();
+        assert_eq!(worker::backlog(), None);
+    }
+    #[test]
+    fn event_position_and_mouse_button_helpers() {
+        let info = MouseInfo {
+            point: Point { x: 3, y: 4 },
+            ..Default::default()
+        };
+        let down = Event::MouseEvent((MouseEvent::MouseRightButtonDown, info.clone()));
+        assert_eq!(down.position(), down.mouse_position());
+        assert_eq!(
+            down.position().map(|point| (point.x, point.y)),
+            Some((3, 4))
+        );
+        assert!(down.is_mouse_button(MouseButton::Right));
+        assert!(!down.is_mouse_button(MouseButton::Left));
+        let wheel = Event::MouseEvent((MouseEvent::MouseWheel, info));
+        assert!(!wheel.is_mouse_button(MouseButton::Left));
+        assert!(wheel.position().is_some());
+        assert!(Event::HookStarted.position().is_none());
+    }
+    #[test]
+    fn hook_start_reports_install_result() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        let mut hook = Hook::new(false, false);
+        hook.start(|_| Dispatch::Pass).unwrap();
+        //返回时已经安装完成
+        assert!(is_running());
+        hook.stop().unwrap();
+        assert!(!is_running());
+        //没有拿到运行标志的线程不发送安装结果，Hook::start据此移除监听器
+        let running = RunningGuard::acquire().unwrap();
+        let (sender, receiver) = sync_channel(1);
+        let failed = spawn_hook(HookConfig::new(), Some(sender));
+        assert!(receiver.recv().is_err());
+        assert!(matches!(
+            failed.join().unwrap(),
+            Err(KbdmsError::AlreadyRunning)
+        ));
+        drop(running);
+    }
+    #[test]
+    fn hotkey_release_clears_blocked_key_while_paused() {
+        let _serial = SERIAL.lock().unwrap_or_else(|err| err.into_inner());
+        *CONFIG.write() = HookConfig::new();
+        KEYS_DOWN.write().clear();
+        let id = register_hotkey_blocking(Modifiers::empty(), vk::F9, || {}).unwrap();
+        let f9 = KBDLLHOOKSTRUCT {
+            vkCode: vk::F9,
+            ..Default::default()
+        };
+        assert!(process_key_input(0, WM_KEYDOWN, &f9));
+        //暂停期间抬起，按下被拦截了，抬起同样拦截
+        pause();
+        assert!(process_key_input(0, WM_KEYUP, &f9));
+        resume();
+        unregister_hotkey(id).unwrap();
+        //记录已经清除，之后的抬起不再被拦截
+        assert!(!process_key_input(0, WM_KEYDOWN, &f9));
+        assert!(!process_key_input(0, WM_KEYUP, &f9));
+        KEYS_DOWN.write().clear();
+    }
 }
@@ -0,0 +1,119 @@
+use crate::{
+    call_guarded,
+    sync::{Mutex, RwLock},
+    Dispatch, Event, MouseEvent, Result,
+};
+use bitflags::bitflags;
+use once_cell::sync::Lazy;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+bitflags! {
+    /// 监听器订阅的事件类型
+    #[derive(Default)]
+    pub struct EventMask: u32 {
+        /// 键盘按下、抬起
+        const KEYBOARD = 0x1;
+        /// 鼠标移动，包括合成的拖动事件
+        const MOUSE_MOVE = 0x2;
+        /// 鼠标按键，包括合成的双击事件
+        const MOUSE_BUTTON = 0x4;
+        /// 垂直、水平滚轮，包括合成的整格滚轮事件
+        const MOUSE_WHEEL = 0x8;
+        /// `HookStarted`/`HookStopped`/`HookReinstalled`
+        const LIFECYCLE = 0x10;
+        /// `CallbackSlow`/`Overload`
+        const DIAGNOSTIC = 0x20;
+    }
+}
+
+impl EventMask {
+    /// 事件所属的类型
+    pub fn of(event: &Event) -> EventMask {
+        match event {
+            Event::KeyEvent(_) => EventMask::KEYBOARD,
+            Event::MouseEvent((event, _)) => match event {
+                MouseEvent::MouseMove
+                | MouseEvent::DragStart
+                | MouseEvent::DragMove
+                | MouseEvent::DragEnd => EventMask::MOUSE_MOVE,
+                MouseEvent::MouseWheel
+                | MouseEvent::MouseHWheel
+                | MouseEvent::MouseWheelNotch
+                | MouseEvent::MouseHWheelNotch => EventMask::MOUSE_WHEEL,
+                _ => EventMask::MOUSE_BUTTON,
+            },
+            Event::HookStarted | Event::HookStopped | Event::HookReinstalled => {
+                EventMask::LIFECYCLE
+            }
+            Event::CallbackSlow { .. } | Event::Overload { .. } => EventMask::DIAGNOSTIC,
+        }
+    }
+}
+
+struct Listener {
+    id: ListenerId,
+    mask: EventMask,
+    removed: AtomicBool,
+    callback: Mutex<Box<dyn FnMut(Event) -> Dispatch + Send>>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static LISTENERS: Lazy<RwLock<Vec<Arc<Listener>>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// 添加事件监听器，可以与`set_hook_callback`设置的回调同时存在
+///
+/// 监听器按添加顺序依次调用，任意一个返回`Dispatch::Block`即拦截该事件。
+pub fn add_listener(
+    callback: impl FnMut(Event) -> Dispatch + Send + 'static,
+) -> Result<ListenerId> {
+    add_listener_with_mask(EventMask::all(), callback)
+}
+
+/// 添加只接收`mask`中类型事件的监听器
+pub fn add_listener_with_mask(
+    mask: EventMask,
+    callback: impl FnMut(Event) -> Dispatch + Send + 'static,
+) -> Result<ListenerId> {
+    let id = ListenerId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    LISTENERS.write().push(Arc::new(Listener {
+        id,
+        mask,
+        removed: AtomicBool::new(false),
+        callback: Mutex::new(Box::new(callback)),
+    }));
+    Ok(id)
+}
+
+/// 移除监听器，可以在监听器回调中调用，移除后不会再收到事件
+pub fn remove_listener(id: ListenerId) -> Result<()> {
+    LISTENERS.write().retain(|listener| {
+        if listener.id == id {
+            listener.removed.store(true, Ordering::Release);
+        }
+        listener.id != id
+    });
+    Ok(())
+}
+
+pub(crate) fn dispatch(event: &Event) -> Dispatch {
+    //复制一份列表后释放锁，监听器中可以添加/移除监听器
+    let listeners = LISTENERS.read().clone();
+    let mask = EventMask::of(event);
+    let mut dispatch = Dispatch::Pass;
+    for listener in listeners {
+        if !listener.mask.intersects(mask) || listener.removed.load(Ordering::Acquire) {
+            continue;
+        }
+        let mut callback = listener.callback.lock();
+        if call_guarded(|| callback(event.clone())) == Some(Dispatch::Block) {
+            dispatch = Dispatch::Block;
+        }
+    }
+    dispatch
+}
@@ -0,0 +1,47 @@
+//! 启用`log`特性时通过log输出诊断信息，target为"kbdmshook"
+//!
+//! 没有启用时这些宏不输出任何内容，也不格式化参数。
+
+macro_rules! debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::debug!(target: "kbdmshook", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::info!(target: "kbdmshook", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+macro_rules! warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::warn!(target: "kbdmshook", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+macro_rules! error {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::error!(target: "kbdmshook", $($arg)*);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
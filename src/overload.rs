@@ -0,0 +1,82 @@
+use crate::{listener::EventMask, sync::Mutex, worker, Event, CONFIG};
+use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
+
+//统计派发速率的时间窗口
+const WINDOW: Duration = Duration::from_secs(1);
+
+struct State {
+    window_start: Instant,
+    //当前窗口内已派发的事件
+    count: u32,
+    //当前窗口内丢弃的事件
+    dropped: u64,
+    //上一个窗口丢弃的事件，等待派发`Event::Overload`
+    summary: Option<u64>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| {
+    Mutex::new(State {
+        window_start: Instant::now(),
+        count: 0,
+        dropped: 0,
+        summary: None,
+    })
+});
+
+pub(crate) fn reset() {
+    let mut state = STATE.lock();
+    state.window_start = Instant::now();
+    state.count = 0;
+    state.dropped = 0;
+    state.summary = None;
+}
+
+/// 过载程度：0正常，1丢弃移动事件，2同时丢弃滚轮事件
+fn level(count: u32, limit: u32) -> u8 {
+    let rate = match count {
+        count if count >= limit.saturating_mul(2) => 2,
+        count if count >= limit => 1,
+        _ => 0,
+    };
+    //回调工作线程的队列积压也视为过载
+    let queue = match worker::backlog().filter(|&(_, capacity)| capacity > 0) {
+        Some((pending, capacity)) if pending * 4 >= capacity * 3 => 2,
+        Some((pending, capacity)) if pending * 2 >= capacity => 1,
+        _ => 0,
+    };
+    rate.max(queue)
+}
+
+/// 是否派发该事件，过载时丢弃移动和滚轮事件，按键事件总是派发
+pub(crate) fn admit(event: &Event) -> bool {
+    let limit = match CONFIG.read().overload_limit {
+        Some(limit) => limit,
+        None => return true,
+    };
+    let mut state = STATE.lock();
+    if state.window_start.elapsed() >= WINDOW {
+        if state.dropped > 0 {
+            *state.summary.get_or_insert(0) += state.dropped;
+        }
+        state.window_start = Instant::now();
+        state.count = 0;
+        state.dropped = 0;
+    }
+    let drop = match EventMask::of(event) {
+        EventMask::MOUSE_MOVE => level(state.count, limit) >= 1,
+        EventMask::MOUSE_WHEEL => level(state.count, limit) >= 2,
+        _ => false,
+    };
+    if drop {
+        state.dropped += 1;
+    } else {
+        state.count += 1;
+    }
+    !drop
+}
+
+/// 上一个时间窗口内丢弃的事件数量，每个窗口只返回一次
+pub(crate) fn take_dropped() -> Option<u64> {
+    STATE.lock().summary.take()
+}
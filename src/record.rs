@@ -0,0 +1,70 @@
+use crate::{input, sync::Mutex, Event, KbdmsError, Result};
+use once_cell::sync::Lazy;
+use std::time::Duration;
+
+/// 录制的事件，`time`为钩子结构体中的时间戳(毫秒)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedEvent {
+    pub time: u32,
+    pub event: Event,
+}
+
+//None表示未在录制
+static RECORDING: Lazy<Mutex<Option<Vec<RecordedEvent>>>> = Lazy::new(|| Mutex::new(None));
+
+/// 开始录制钩子收到的事件，会清空之前未取出的录制内容
+pub fn start_recording() -> Result<()> {
+    RECORDING.lock().replace(Vec::new());
+    Ok(())
+}
+
+/// 停止录制并返回录制到的事件
+pub fn stop_recording() -> Result<Vec<RecordedEvent>> {
+    Ok(RECORDING.lock().take().unwrap_or_default())
+}
+
+pub(crate) fn record(event: &Event) {
+    //只录制输入事件，生命周期、诊断事件无法回放
+    if !matches!(event, Event::KeyEvent(_) | Event::MouseEvent(_)) {
+        return;
+    }
+    let mut recording = RECORDING.lock();
+    if let Some(events) = recording.as_mut() {
+        events.push(RecordedEvent {
+            time: event.time(),
+            event: event.clone(),
+        });
+    }
+}
+
+/// 回放时两个事件之间等待的时间，时间戳倒退(手动编辑或重新排序的录制)时不等待
+pub(crate) fn replay_delay(last_time: u32, time: u32, speed: f32) -> Duration {
+    //GetTickCount约49.7天回绕一次，按有符号差值比较先后
+    let elapsed = u32::wrapping_sub(time, last_time) as i32;
+    if elapsed <= 0 {
+        return Duration::ZERO;
+    }
+    //speed很小时可能超出Duration的范围
+    Duration::try_from_secs_f32(elapsed as f32 / 1000.0 / speed).unwrap_or(Duration::MAX)
+}
+
+/// 通过SendInput回放录制的事件，按`speed`倍速保持事件间隔
+///
+/// 回放的事件由SendInput注入，钩子收到时`injected`为true，可以据此与真实输入区分。
+pub fn replay(events: &[RecordedEvent], speed: f32) -> Result<()> {
+    if !(speed > 0.0 && speed.is_finite()) {
+        return Err(KbdmsError::InvalidSpeed(speed));
+    }
+    let mut last_time = None;
+    for recorded in events {
+        if let Some(last_time) = last_time {
+            std::thread::sleep(replay_delay(last_time, recorded.time, speed));
+        }
+        last_time = Some(recorded.time);
+        if let Some(input) = input::event_input(&recorded.event) {
+            input::send_inputs(&[input])?;
+        }
+    }
+    Ok(())
+}
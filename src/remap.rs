@@ -0,0 +1,82 @@
+use crate::{
+    chord::is_part_of,
+    input::{scan_code, send_inputs, tagged_keyboard_input},
+    sync::{Mutex, RwLock},
+    Result,
+};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// 重映射注入的按键在dwExtraInfo中携带的标记，与`INJECTED_EXTRA_INFO`不同，
+/// 开启`HookConfig::ignore_own_injected`时重映射后的按键仍然会派发
+pub const REMAPPED_EXTRA_INFO: usize = 0x4B42_524D; //"KBRM"
+
+//原始按键 -> 目标按键
+static REMAPS: Lazy<RwLock<HashMap<u32, u32>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+//已注入按下、还没有抬起的按键，抬起时按按下时的映射注入，中途修改映射不会导致目标按键一直按下
+static ACTIVE: Lazy<Mutex<HashMap<u32, u32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 把按键`from`重映射为`to`，例如把Caps Lock映射为Esc
+///
+/// 钩子拦截`from`的按下和抬起，分别通过SendInput注入`to`的按下和抬起，按住时的自动重复同样转换。
+/// 回调收到的是注入的`to`(dwExtraInfo为`REMAPPED_EXTRA_INFO`)，收不到原始的`from`；
+/// 注入的按键不会再次被重映射，A映射为B、B映射为A时不会循环。
+/// `vk::SHIFT`等不区分左右的`from`匹配任意一侧。暂停期间按下的按键不重映射，
+/// 暂停前按下的按键抬起时仍然注入目标按键的抬起。
+pub fn set_remap(from: u32, to: u32) -> Result<()> {
+    REMAPS.write().insert(from, to);
+    Ok(())
+}
+
+/// 取消`from`的重映射
+pub fn clear_remap(from: u32) -> Result<()> {
+    REMAPS.write().remove(&from);
+    Ok(())
+}
+
+fn target(vk: u32) -> Option<u32> {
+    let remaps = REMAPS.read();
+    if let Some(&to) = remaps.get(&vk) {
+        return Some(to);
+    }
+    remaps
+        .iter()
+        .find(|(&from, _)| is_part_of(from, vk))
+        .map(|(_, &to)| to)
+}
+
+/// 注入所有已按下的目标按键的抬起，卸载钩子后收不到原始按键的抬起
+pub(crate) fn release_all() {
+    let inputs: Vec<_> = ACTIVE
+        .lock()
+        .drain()
+        .map(|(_, to)| tagged_keyboard_input(to, scan_code(to), false, REMAPPED_EXTRA_INFO))
+        .collect();
+    let _ = send_inputs(&inputs);
+}
+
+/// 按键需要重映射时注入目标按键并返回true，调用者应拦截原始按键；注入失败时返回false，原始按键照常传递
+pub(crate) fn remap(vk: u32, down: bool, extra_info: usize) -> bool {
+    if extra_info == REMAPPED_EXTRA_INFO {
+        return false;
+    }
+    let to = if down {
+        match target(vk) {
+            Some(to) => *ACTIVE.lock().entry(vk).or_insert(to),
+            None => return false,
+        }
+    } else {
+        //按下时没有重映射的按键抬起时同样不重映射
+        match ACTIVE.lock().remove(&vk) {
+            Some(to) => to,
+            None => return false,
+        }
+    };
+    let input = tagged_keyboard_input(to, scan_code(to), down, REMAPPED_EXTRA_INFO);
+    let sent = send_inputs(&[input]).is_ok();
+    if down && !sent {
+        //原始按键照常传递，抬起时也不能再拦截
+        ACTIVE.lock().remove(&vk);
+    }
+    sent
+}
@@ -0,0 +1,121 @@
+use crate::{ButtonAction, MouseButton, MouseEvent, MouseInfo};
+use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering};
+use windows::Win32::{
+    System::SystemInformation::GetTickCount, UI::WindowsAndMessaging::WHEEL_DELTA,
+};
+
+/// 钩子收到的事件统计，包括被过滤、没有派发给回调的事件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookStats {
+    /// 按键按下次数，不包括自动重复
+    pub key_presses: u64,
+    /// 左键按下次数
+    pub left_clicks: u64,
+    /// 右键按下次数
+    pub right_clicks: u64,
+    /// 中键按下次数
+    pub middle_clicks: u64,
+    /// 侧键1按下次数
+    pub x1_clicks: u64,
+    /// 侧键2按下次数
+    pub x2_clicks: u64,
+    /// 垂直和水平滚轮滚动的总格数(取绝对值)
+    pub wheel_notches: f64,
+    /// 上一个完整的1秒内收到的事件数
+    pub events_per_second: u32,
+}
+
+static KEY_PRESSES: AtomicU64 = AtomicU64::new(0);
+//Left, Right, Middle, X1, X2
+static CLICKS: [AtomicU64; 5] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+static WHEEL_DELTA_SUM: AtomicI64 = AtomicI64::new(0);
+//按秒统计事件数：当前秒(GetTickCount / 1000)、当前秒的事件数、上一秒的事件数
+static SECOND: AtomicU32 = AtomicU32::new(0);
+static SECOND_EVENTS: AtomicU32 = AtomicU32::new(0);
+static LAST_SECOND_EVENTS: AtomicU32 = AtomicU32::new(0);
+
+fn count_event(time: u32) {
+    let second = time / 1000;
+    let current = SECOND.load(Ordering::Relaxed);
+    if second != current
+        && SECOND
+            .compare_exchange(current, second, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    {
+        //中间有空闲的秒时上一秒没有事件
+        let events = SECOND_EVENTS.swap(0, Ordering::Relaxed);
+        let last = if second == current.wrapping_add(1) {
+            events
+        } else {
+            0
+        };
+        LAST_SECOND_EVENTS.store(last, Ordering::Relaxed);
+    }
+    SECOND_EVENTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub(crate) fn count_key(down: bool, is_repeat: bool, time: u32) {
+    count_event(time);
+    if down && !is_repeat {
+        KEY_PRESSES.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn count_mouse(event: MouseEvent, info: &MouseInfo) {
+    count_event(info.time);
+    if let Some((button, ButtonAction::Down)) = event.button() {
+        let index = match button {
+            MouseButton::Left => 0,
+            MouseButton::Right => 1,
+            MouseButton::Middle => 2,
+            MouseButton::X1 => 3,
+            MouseButton::X2 => 4,
+        };
+        CLICKS[index].fetch_add(1, Ordering::Relaxed);
+    }
+    if info.wheel_delta != 0 {
+        WHEEL_DELTA_SUM.fetch_add((info.wheel_delta as i64).abs(), Ordering::Relaxed);
+    }
+}
+
+/// 读取事件统计，计数使用原子变量维护，不会阻塞钩子线程
+pub fn stats() -> HookStats {
+    let clicks = |index: usize| CLICKS[index].load(Ordering::Relaxed);
+    //超过1秒没有事件时上一秒的统计已经过期
+    let now = unsafe { GetTickCount() } / 1000;
+    let second = SECOND.load(Ordering::Relaxed);
+    let events_per_second = if now == second {
+        LAST_SECOND_EVENTS.load(Ordering::Relaxed)
+    } else if now == second.wrapping_add(1) {
+        SECOND_EVENTS.load(Ordering::Relaxed)
+    } else {
+        0
+    };
+    HookStats {
+        key_presses: KEY_PRESSES.load(Ordering::Relaxed),
+        left_clicks: clicks(0),
+        right_clicks: clicks(1),
+        middle_clicks: clicks(2),
+        x1_clicks: clicks(3),
+        x2_clicks: clicks(4),
+        wheel_notches: WHEEL_DELTA_SUM.load(Ordering::Relaxed) as f64 / WHEEL_DELTA as f64,
+        events_per_second,
+    }
+}
+
+/// 清零事件统计
+pub fn reset_stats() {
+    KEY_PRESSES.store(0, Ordering::Relaxed);
+    for clicks in &CLICKS {
+        clicks.store(0, Ordering::Relaxed);
+    }
+    WHEEL_DELTA_SUM.store(0, Ordering::Relaxed);
+    SECOND_EVENTS.store(0, Ordering::Relaxed);
+    LAST_SECOND_EVENTS.store(0, Ordering::Relaxed);
+}
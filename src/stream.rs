@@ -0,0 +1,36 @@
+use crate::{start_hook_async, sync::Mutex, Event, HookHandle, Result, EVENT_CHANNEL_CAPACITY};
+use once_cell::sync::Lazy;
+use tokio::sync::mpsc::{self, error::TrySendError, Sender};
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+
+static STREAM_SENDER: Lazy<Mutex<Option<Sender<Event>>>> = Lazy::new(|| Mutex::new(None));
+
+/// 启动钩子，并以异步流的方式接收事件(需要`tokio`特性)
+///
+/// 钩子和消息循环仍然运行在单独的系统线程中，不占用异步运行时的线程。
+/// 通道容量为 [`EVENT_CHANNEL_CAPACITY`]，消费者处理不及时导致通道已满时丢弃最新的事件，
+/// 钩子线程不会等待。流被drop后不再发送事件，钩子需要通过返回的句柄或`stop_hook`停止。
+pub fn event_stream(
+    hook_mouse: bool,
+    hook_keyboard: bool,
+) -> Result<(HookHandle, impl Stream<Item = Event>)> {
+    let (sender, receiver) = mpsc::channel(EVENT_CHANNEL_CAPACITY);
+    STREAM_SENDER.lock().replace(sender);
+    Ok((
+        start_hook_async(hook_mouse, hook_keyboard),
+        ReceiverStream::new(receiver),
+    ))
+}
+
+pub(crate) fn send(event: &Event) {
+    let mut sender = STREAM_SENDER.lock();
+    if let Some(tx) = sender.as_ref() {
+        match tx.try_send(event.clone()) {
+            //通道已满，丢弃最新的事件
+            Ok(()) | Err(TrySendError::Full(_)) => (),
+            Err(TrySendError::Closed(_)) => {
+                sender.take();
+            }
+        }
+    }
+}
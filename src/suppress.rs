@@ -0,0 +1,62 @@
+use crate::{debounce::Source, sync::Mutex, Result, KEYS_DOWN};
+use once_cell::sync::Lazy;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct State {
+    remaining: usize,
+    deadline: Option<Instant>,
+    //被拦截了按下事件的按键，抬起时同样拦截
+    blocked: HashSet<Source>,
+}
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+/// 拦截接下来`n`次真实的按键按下、鼠标按键按下或滚轮事件，不派发给回调也不传递给其他程序
+///
+/// 用于注入一段输入前吃掉用户误触的按键。鼠标移动和注入的事件不计数也不拦截；
+/// 被拦截的按下事件对应的抬起事件和重复按下同样拦截，不计入`n`；调用时已经按住的按键的重复按下
+/// 不计数也不拦截。超过`timeout`后剩余的次数作废，
+/// 避免次数估计错误时一直拦截输入。再次调用会覆盖之前的设置，`n`为0表示取消。
+pub fn suppress_next(n: usize, timeout: Duration) -> Result<()> {
+    let mut state = STATE.lock();
+    state.remaining = n;
+    state.deadline = Some(Instant::now() + timeout);
+    Ok(())
+}
+
+/// 判断是否拦截本次事件，`source`为None表示不需要配对抬起事件的滚轮
+pub(crate) fn should_suppress(source: Option<Source>, down: bool) -> bool {
+    let mut state = STATE.lock();
+    if !down {
+        return source.is_some_and(|source| state.blocked.remove(&source));
+    }
+    if let Some(source) = source {
+        //已经拦截了按下的按键，按住不放产生的重复按下同样拦截
+        if state.blocked.contains(&source) {
+            return true;
+        }
+        //已经按住的按键的重复按下不拦截，否则抬起也会被拦截，按键一直处于按下状态
+        if let Source::Key(vk) = source {
+            if KEYS_DOWN.read().contains_key(&vk) {
+                return false;
+            }
+        }
+    }
+    if state.remaining == 0 {
+        return false;
+    }
+    if state
+        .deadline
+        .is_some_and(|deadline| Instant::now() >= deadline)
+    {
+        state.remaining = 0;
+        return false;
+    }
+    state.remaining -= 1;
+    if let Some(source) = source {
+        state.blocked.insert(source);
+    }
+    true
+}
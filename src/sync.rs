@@ -0,0 +1,48 @@
+//! 不会被毒化的锁
+//!
+//! 钩子回调在系统线程上反复执行，标准库的锁被panic毒化后每次加锁都会失败，钩子会一直不可用。
+//! 这里的锁在毒化后直接取回其中的数据继续使用；锁内的状态都是简单的集合和标志，
+//! 被中断的修改最多丢失一次事件的状态更新。
+//!
+//! 开启`parking_lot`特性时改用parking_lot的锁，本身没有毒化，加锁开销也更低。
+
+#[cfg(feature = "parking_lot")]
+pub(crate) use parking_lot::{Mutex, RwLock};
+
+#[cfg(not(feature = "parking_lot"))]
+pub(crate) use std_locks::{Mutex, RwLock};
+
+#[cfg(not(feature = "parking_lot"))]
+mod std_locks {
+    use std::sync::{self, MutexGuard, PoisonError, RwLockReadGuard, RwLockWriteGuard};
+
+    #[derive(Debug, Default)]
+    pub(crate) struct Mutex<T>(sync::Mutex<T>);
+
+    impl<T> Mutex<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            Mutex(sync::Mutex::new(value))
+        }
+
+        pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+            self.0.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    #[derive(Debug, Default)]
+    pub(crate) struct RwLock<T>(sync::RwLock<T>);
+
+    impl<T> RwLock<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            RwLock(sync::RwLock::new(value))
+        }
+
+        pub(crate) fn read(&self) -> RwLockReadGuard<'_, T> {
+            self.0.read().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        pub(crate) fn write(&self) -> RwLockWriteGuard<'_, T> {
+            self.0.write().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+}
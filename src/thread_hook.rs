@@ -0,0 +1,80 @@
+use crate::{installed_hook, process_key_input, process_mouse_input, KEYBOARD_HOOK, MOUSE_HOOK};
+use windows::Win32::{
+    Foundation::{LPARAM, LRESULT, WPARAM},
+    UI::WindowsAndMessaging::{
+        CallNextHookEx, GetMessageExtraInfo, GetMessageTime, HC_ACTION, KBDLLHOOKSTRUCT,
+        KBDLLHOOKSTRUCT_FLAGS, LLKHF_ALTDOWN, LLKHF_EXTENDED, LLKHF_UP, MOUSEHOOKSTRUCTEX,
+        MSLLHOOKSTRUCT, WM_KEYDOWN, WM_KEYUP, WM_LBUTTONDBLCLK, WM_LBUTTONDOWN, WM_MBUTTONDBLCLK,
+        WM_MBUTTONDOWN, WM_RBUTTONDBLCLK, WM_RBUTTONDOWN, WM_SYSKEYDOWN, WM_SYSKEYUP,
+        WM_XBUTTONDBLCLK, WM_XBUTTONDOWN,
+    },
+};
+
+/// WH_KEYBOARD钩子函数
+///
+/// wParam是虚拟键码，lParam是按键消息的lParam：0-15位重复次数，16-23位扫描码，24位扩展键，
+/// 29位Alt按下，30位之前的状态，31位抬起。这里转换为KBDLLHOOKSTRUCT后按低级钩子处理，
+/// 注入标志无法得到，总是视为物理输入，时间和附加信息取自GetMessageTime/GetMessageExtraInfo。
+pub(crate) unsafe extern "system" fn keyboard_proc(
+    code: i32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    //HC_NOREMOVE表示消息没有从队列中取出，之后还会再收到一次
+    if code == HC_ACTION as i32 {
+        let bits = lparam.0 as u32;
+        //24、29、31位右移24位后正好是LLKHF_EXTENDED、LLKHF_ALTDOWN和LLKHF_UP
+        let flags = (bits >> 24) & (LLKHF_EXTENDED.0 | LLKHF_ALTDOWN.0 | LLKHF_UP.0);
+        let data = KBDLLHOOKSTRUCT {
+            vkCode: wparam.0 as u32,
+            scanCode: (bits >> 16) & 0xFF,
+            flags: KBDLLHOOKSTRUCT_FLAGS(flags),
+            time: GetMessageTime() as u32,
+            dwExtraInfo: GetMessageExtraInfo().0 as usize,
+        };
+        let msg = match (flags & LLKHF_UP.0 != 0, flags & LLKHF_ALTDOWN.0 != 0) {
+            (false, false) => WM_KEYDOWN,
+            (false, true) => WM_SYSKEYDOWN,
+            (true, false) => WM_KEYUP,
+            (true, true) => WM_SYSKEYUP,
+        };
+        if process_key_input(0, msg, &data) {
+            return LRESULT(1);
+        }
+    }
+    CallNextHookEx(installed_hook(&KEYBOARD_HOOK), code, wparam, lparam)
+}
+
+/// WH_MOUSE钩子函数
+///
+/// wParam是鼠标消息，lParam指向MOUSEHOOKSTRUCTEX，坐标同样是屏幕坐标。只能收到发给目标线程窗口的
+/// 消息，非客户区消息(WM_NC*)不处理；窗口类带CS_DBLCLKS时第二次按下变为双击消息，
+/// 这里还原为按下消息，双击事件仍由钩子合成。
+pub(crate) unsafe extern "system" fn mouse_proc(
+    code: i32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let hook = lparam.0 as *const MOUSEHOOKSTRUCTEX;
+    if code == HC_ACTION as i32 && !hook.is_null() {
+        let hook = &*hook;
+        let data = MSLLHOOKSTRUCT {
+            pt: hook.Base.pt,
+            mouseData: hook.mouseData,
+            flags: 0,
+            time: GetMessageTime() as u32,
+            dwExtraInfo: hook.Base.dwExtraInfo,
+        };
+        let msg = match wparam.0 as u32 {
+            WM_LBUTTONDBLCLK => WM_LBUTTONDOWN,
+            WM_RBUTTONDBLCLK => WM_RBUTTONDOWN,
+            WM_MBUTTONDBLCLK => WM_MBUTTONDOWN,
+            WM_XBUTTONDBLCLK => WM_XBUTTONDOWN,
+            msg => msg,
+        };
+        if process_mouse_input(0, msg, &data) {
+            return LRESULT(1);
+        }
+    }
+    CallNextHookEx(installed_hook(&MOUSE_HOOK), code, wparam, lparam)
+}
@@ -0,0 +1,76 @@
+use crate::{is_running, HookKind, CONFIG, KEYBOARD_HOOK, MOUSE_HOOK};
+use std::sync::atomic::{AtomicU32, Ordering};
+use windows::Win32::{
+    System::SystemInformation::GetTickCount,
+    UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+};
+
+//系统记录输入之后钩子函数被调用前允许的延迟(毫秒)
+const GRACE_MS: i32 = 1000;
+
+//键盘、鼠标钩子函数各自最后一次被调用的时间(GetTickCount)
+static LAST_KEYBOARD_CALL: AtomicU32 = AtomicU32::new(0);
+static LAST_MOUSE_CALL: AtomicU32 = AtomicU32::new(0);
+
+fn last_call(kind: HookKind) -> &'static AtomicU32 {
+    match kind {
+        HookKind::Keyboard => &LAST_KEYBOARD_CALL,
+        HookKind::Mouse => &LAST_MOUSE_CALL,
+    }
+}
+
+/// 在钩子函数入口调用
+pub(crate) fn touch(kind: HookKind) {
+    last_call(kind).store(unsafe { GetTickCount() }, Ordering::Relaxed);
+}
+
+/// 安装或重新安装钩子后调用，之前的输入不计入
+pub(crate) fn reset() {
+    touch(HookKind::Keyboard);
+    touch(HookKind::Mouse);
+}
+
+/// 系统最后一次输入的时间为`last_input`时，`last_call`对应的钩子函数是否在允许的延迟内被调用过
+pub(crate) fn saw_input(last_input: u32, last_call: u32) -> bool {
+    //GetTickCount约49.7天回绕一次，按有符号差值比较先后
+    (u32::wrapping_sub(last_input, last_call) as i32) <= GRACE_MS
+}
+
+/// 检查钩子是否仍然有效，钩子没有运行时返回false
+///
+/// 钩子函数超时等原因被系统卸载时不会有任何通知，`is_running`仍然返回true。
+/// 这里比较系统最后一次输入的时间(GetLastInputInfo)和钩子函数最后一次被调用的时间，
+/// 键盘、鼠标钩子分别记录最后一次被调用的时间，系统收到输入后超过1秒没有任何已安装的钩子被调用，
+/// 说明这次输入来自的设备的钩子已被卸载。系统最后输入时间不区分设备，只要有一个钩子看到了最后一次输入
+/// 就视为正常，另一个钩子要等到它的设备产生最后一次输入时才能检查出来。
+///
+/// 以下情况无法判断，总是返回true：
+/// - 只安装了一种钩子：另一种设备的输入同样会更新系统最后输入时间，无法和钩子被卸载区分；
+/// - 使用`HookConfig::target_thread`安装线程钩子：收不到其他线程的输入。
+///
+/// 提升权限的窗口在前台时钩子收不到输入，也可能返回false。
+pub fn verify_hooks() -> bool {
+    if !is_running() {
+        return false;
+    }
+    if KEYBOARD_HOOK.read().is_none()
+        || MOUSE_HOOK.read().is_none()
+        || CONFIG.read().target_thread.is_some()
+    {
+        return true;
+    }
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return true;
+    }
+    //刚收到的输入钩子函数可能还没被调用
+    if unsafe { GetTickCount() }.wrapping_sub(info.dwTime) < GRACE_MS as u32 {
+        return true;
+    }
+    [HookKind::Keyboard, HookKind::Mouse]
+        .into_iter()
+        .any(|kind| saw_input(info.dwTime, last_call(kind).load(Ordering::Relaxed)))
+}
@@ -0,0 +1,65 @@
+use crate::{sync::Mutex, MouseEvent, CONFIG};
+use once_cell::sync::Lazy;
+use windows::Win32::UI::WindowsAndMessaging::WHEEL_DELTA;
+
+/// 滚轮滚动的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WheelDirection {
+    /// 向前(远离用户)滚动
+    Up,
+    /// 向后(朝向用户)滚动
+    Down,
+    /// 水平滚轮向左
+    Left,
+    /// 水平滚轮向右
+    Right,
+}
+
+/// 滚轮事件的滚动方向，非滚轮事件或滚动量为0时返回None
+pub(crate) fn direction(event: MouseEvent, delta: i16) -> Option<WheelDirection> {
+    let horizontal = match event {
+        MouseEvent::MouseWheel | MouseEvent::MouseWheelNotch => false,
+        MouseEvent::MouseHWheel | MouseEvent::MouseHWheelNotch => true,
+        _ => return None,
+    };
+    Some(match (horizontal, delta.signum()) {
+        (_, 0) => return None,
+        (false, 1) => WheelDirection::Up,
+        (false, _) => WheelDirection::Down,
+        (true, 1) => WheelDirection::Right,
+        (true, _) => WheelDirection::Left,
+    })
+}
+
+//(垂直, 水平)滚轮累计的滚动量
+static ACCUMULATED: Lazy<Mutex<(i32, i32)>> = Lazy::new(|| Mutex::new((0, 0)));
+
+pub(crate) fn reset() {
+    *ACCUMULATED.lock() = (0, 0);
+}
+
+/// 累计滚轮滚动量，每满一格(WHEEL_DELTA)返回需要额外派发的整格事件和滚动量，余数留到下一次
+pub(crate) fn detect(event: MouseEvent, delta: i16) -> Option<(MouseEvent, i16)> {
+    let notch_event = match event {
+        MouseEvent::MouseWheel => MouseEvent::MouseWheelNotch,
+        MouseEvent::MouseHWheel => MouseEvent::MouseHWheelNotch,
+        _ => return None,
+    };
+    if !CONFIG.read().wheel_notches {
+        return None;
+    }
+    let mut accumulated = ACCUMULATED.lock();
+    let total = match event {
+        MouseEvent::MouseWheel => &mut accumulated.0,
+        _ => &mut accumulated.1,
+    };
+    *total += delta as i32;
+    let notches = *total / WHEEL_DELTA as i32;
+    if notches == 0 {
+        return None;
+    }
+    *total -= notches * WHEEL_DELTA as i32;
+    let delta = (notches * WHEEL_DELTA as i32).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+    Some((notch_event, delta))
+}
@@ -0,0 +1,131 @@
+use crate::{sync::Mutex, Point};
+use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
+use windows::{
+    core::PWSTR,
+    Win32::{
+        Foundation::{CloseHandle, HWND, POINT},
+        Graphics::Gdi::ScreenToClient,
+        System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        },
+        UI::WindowsAndMessaging::{
+            GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId, WindowFromPoint,
+        },
+    },
+};
+
+//前台窗口检查结果的缓存时间，避免每个事件都查询进程
+const FOREGROUND_CACHE: Duration = Duration::from_millis(100);
+
+//(检查时间, 是否匹配)
+static FOREGROUND: Lazy<Mutex<Option<(Instant, bool)>>> = Lazy::new(|| Mutex::new(None));
+
+/// 鼠标按下位置的窗口信息(需要开启`HookConfig::window_info`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowInfo {
+    /// WindowFromPoint返回的窗口句柄，可能是按钮等子窗口
+    pub hwnd: isize,
+    /// 窗口标题
+    pub title: String,
+    /// 窗口所属进程id
+    pub process_id: u32,
+}
+
+pub(crate) fn window_at(point: &Point) -> Option<WindowInfo> {
+    unsafe {
+        let hwnd = WindowFromPoint(POINT {
+            x: point.x,
+            y: point.y,
+        });
+        if hwnd.0 == 0 {
+            return None;
+        }
+        let mut title = [0u16; 256];
+        let len = GetWindowTextW(hwnd, &mut title).max(0) as usize;
+        let mut process_id = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        Some(WindowInfo {
+            hwnd: hwnd.0,
+            title: String::from_utf16_lossy(&title[..len]),
+            process_id,
+        })
+    }
+}
+
+/// 屏幕坐标转换为前台窗口客户区坐标，没有前台窗口或转换失败时返回None
+pub(crate) fn client_pos(point: &Point) -> Option<Point> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0 == 0 {
+            return None;
+        }
+        let mut pos = POINT {
+            x: point.x,
+            y: point.y,
+        };
+        if !ScreenToClient(hwnd, &mut pos).as_bool() {
+            return None;
+        }
+        Some(Point { x: pos.x, y: pos.y })
+    }
+}
+
+/// 匹配前台窗口的方式(`HookConfig::only_when_foreground`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProcessMatch {
+    /// 前台窗口所属进程的可执行文件名，如`notepad.exe`，不区分大小写
+    ExeName(String),
+    /// 前台窗口的句柄
+    Hwnd(isize),
+}
+
+pub(crate) fn reset_foreground() {
+    FOREGROUND.lock().take();
+}
+
+/// 前台窗口是否匹配`target`，结果缓存100ms
+pub(crate) fn foreground_matches(target: &ProcessMatch) -> bool {
+    let mut cached = FOREGROUND.lock();
+    if let Some((time, matches)) = *cached {
+        if time.elapsed() < FOREGROUND_CACHE {
+            return matches;
+        }
+    }
+    let hwnd = unsafe { GetForegroundWindow() };
+    let matches = match target {
+        ProcessMatch::Hwnd(target) => hwnd.0 != 0 && hwnd.0 == *target,
+        ProcessMatch::ExeName(name) => {
+            hwnd.0 != 0
+                && exe_name(hwnd).is_some_and(|exe| exe.to_lowercase() == name.to_lowercase())
+        }
+    };
+    cached.replace((Instant::now(), matches));
+    matches
+}
+
+/// 窗口所属进程的可执行文件名，没有权限查询(如提升权限的进程)时返回None
+fn exe_name(hwnd: HWND) -> Option<String> {
+    unsafe {
+        let mut process_id = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+        let mut path = [0u16; 1024];
+        let mut len = path.len() as u32;
+        let ok = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(path.as_mut_ptr()),
+            &mut len,
+        )
+        .as_bool();
+        CloseHandle(process);
+        if !ok {
+            return None;
+        }
+        let path = String::from_utf16_lossy(&path[..len as usize]);
+        path.rsplit('\\').next().map(str::to_string)
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{call_guarded, sync::Mutex, Event, KbdmsError, Result, CALLBACK};
+use once_cell::sync::Lazy;
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    mpsc::{sync_channel, SyncSender, TrySendError},
+};
+
+static SENDER: Lazy<Mutex<Option<SyncSender<Event>>>> = Lazy::new(|| Mutex::new(None));
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+//已入队、回调还没有处理完的事件数量和队列容量
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+static CAPACITY: AtomicUsize = AtomicUsize::new(0);
+
+/// 启动回调工作线程，替换旧的队列后旧线程处理完剩余事件自行退出
+///
+/// 容量为0时按1处理，否则同步通道没有缓冲，try_send总是失败。创建线程失败时保留旧的队列。
+pub(crate) fn start(capacity: usize) -> Result<()> {
+    let capacity = capacity.max(1);
+    let (tx, rx) = sync_channel::<Event>(capacity);
+    std::thread::Builder::new()
+        .name("kbdmshook-callback".to_string())
+        .spawn(move || {
+            for event in rx {
+                if let Some(callback) = CALLBACK.lock().as_mut() {
+                    call_guarded(|| callback(event));
+                }
+                //旧线程退出前仍在处理时计数可能已被新队列清零
+                let _ = PENDING
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+            }
+        })
+        .map_err(|err| KbdmsError::WorkerSpawn(err.kind()))?;
+    SENDER.lock().replace(tx);
+    DROPPED.store(0, Ordering::Relaxed);
+    PENDING.store(0, Ordering::Relaxed);
+    CAPACITY.store(capacity, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 关闭队列，工作线程处理完已入队的事件后退出
+pub(crate) fn stop() {
+    SENDER.lock().take();
+}
+
+/// 把事件交给工作线程，没有启用工作线程时原样返回事件，由调用者同步调用回调
+pub(crate) fn try_send(event: Event) -> Option<Event> {
+    let mut sender = SENDER.lock();
+    let tx = match sender.as_ref() {
+        Some(tx) => tx,
+        None => return Some(event),
+    };
+    match tx.try_send(event) {
+        Ok(()) => {
+            PENDING.fetch_add(1, Ordering::Relaxed);
+        }
+        //队列已满，丢弃最新的事件，不阻塞钩子线程
+        Err(TrySendError::Full(_)) => {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(TrySendError::Disconnected(_)) => {
+            sender.take();
+        }
+    }
+    None
+}
+
+/// 队列中等待处理的事件数量和队列容量，没有启用工作线程时返回None
+pub(crate) fn backlog() -> Option<(usize, usize)> {
+    SENDER.lock().as_ref()?;
+    Some((
+        PENDING.load(Ordering::Relaxed),
+        CAPACITY.load(Ordering::Relaxed),
+    ))
+}
+
+/// 启用`HookConfig::callback_worker`后因队列已满被丢弃、没有交给回调的事件数量，每次启动钩子时清零
+pub fn dropped_callback_events() -> u64 {
+    DROPPED.load(Ordering::Relaxed)
+}